@@ -1,31 +1,121 @@
-use crate::utils::read_to_vec;
+use std::cmp::Reverse;
 
-fn to_item(line: &str) -> Option<i32> {
-    match line.trim() {
-        "" => None,
-        val => Some(val.parse().unwrap()),
+use crate::utils::{read_groups, TopK};
+
+#[allow(dead_code)]
+struct Stats {
+    count: usize,
+    mean: f64,
+    median: f64,
+    histogram: Vec<(i64, usize)>,
+}
+
+fn to_item(line: &str) -> i64 {
+    line.trim().parse().unwrap()
+}
+
+fn top_elves(totals: &[i64], k: usize) -> Vec<(i64, usize)> {
+    let mut top = TopK::new(k);
+    top.extend(
+        totals
+            .iter()
+            .enumerate()
+            .map(|(elf, total)| (*total, Reverse(elf + 1))),
+    );
+    top.into_sorted_vec()
+        .into_iter()
+        .map(|(total, elf)| (total, elf.0))
+        .collect()
+}
+
+#[allow(dead_code)]
+fn stats(totals: &[i64], width: i64) -> Option<Stats> {
+    assert!(
+        width > 0,
+        "histogram buckets must be at least one calorie wide"
+    );
+    if totals.is_empty() {
+        return None;
     }
+    let mut sorted = totals.to_vec();
+    sorted.sort_unstable();
+    let count = sorted.len();
+    let median = match count % 2 {
+        0 => (sorted[count / 2 - 1] + sorted[count / 2]) as f64 / 2.0,
+        _ => sorted[count / 2] as f64,
+    };
+    Some(Stats {
+        count,
+        mean: sorted.iter().sum::<i64>() as f64 / count as f64,
+        median,
+        histogram: histogram(&sorted, width),
+    })
 }
 
-pub fn run() {
-    let items = read_to_vec("./data/day01.txt", to_item);
-
-    let mut calories: Vec<i32> = vec![];
-    let mut sum = 0i32;
-    for item in items {
-        match item {
-            Some(value) => sum += value,
-            None => {
-                calories.push(sum);
-                sum = 0
-            }
+fn histogram(sorted: &[i64], width: i64) -> Vec<(i64, usize)> {
+    let mut buckets: Vec<(i64, usize)> = vec![];
+    for total in sorted {
+        let start = total.div_euclid(width) * width;
+        match buckets.last_mut() {
+            Some((bucket, count)) if *bucket == start => *count += 1,
+            _ => buckets.push((start, 1)),
         }
     }
-    calories.push(sum);
-    calories.sort_by(|a, b| b.cmp(a));
+    buckets
+}
+
+pub fn run() {
+    let totals: Vec<i64> = read_groups("./data/day01.txt", to_item)
+        .iter()
+        .map(|group| group.iter().sum())
+        .collect();
+    let top = top_elves(&totals, 3);
 
-    let max_values = &calories[0..3];
     println!("== Day 01 ==");
-    println!("Part 1: {}", max_values[0]);
-    println!("Part 2: {}", max_values[0] + max_values[1] + max_values[2]);
+    match top.first() {
+        Some((total, _)) => println!("Part 1: {}", total),
+        None => println!("Part 1 error: no elves"),
+    }
+    println!(
+        "Part 2: {}",
+        top.iter().map(|(total, _)| total).sum::<i64>()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_elves_break_ties_by_elf_number() {
+        let totals = [6000, 4000, 11000, 24000, 10000, 11000];
+        assert_eq!(top_elves(&totals, 3), [(24000, 4), (11000, 3), (11000, 6)]);
+        assert_eq!(top_elves(&totals, 10).len(), 6);
+        assert!(top_elves(&totals, 0).is_empty());
+        assert!(top_elves(&[], 3).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn histogram_needs_positive_width() {
+        stats(&[1, 2, 3], 0);
+    }
+
+    #[test]
+    fn stats_of_totals() {
+        assert!(stats(&[], 10).is_none());
+        let odd = stats(&[3, 1, 8], 5).unwrap();
+        assert_eq!((odd.count, odd.mean, odd.median), (3, 4.0, 3.0));
+        assert_eq!(odd.histogram, [(0, 2), (5, 1)]);
+        let even = stats(&[4, 1, 2, 7], 1).unwrap();
+        assert_eq!((even.count, even.mean, even.median), (4, 3.5, 3.0));
+        assert_eq!(even.histogram, [(1, 1), (2, 1), (4, 1), (7, 1)]);
+        let example = stats(&[6000, 4000, 11000, 24000, 10000], 5000).unwrap();
+        assert_eq!(
+            example.histogram,
+            [(0, 1), (5000, 1), (10000, 2), (20000, 1)]
+        );
+        let negative = stats(&[-3, -1, 0, 2], 2).unwrap();
+        assert_eq!(negative.histogram, [(-4, 1), (-2, 1), (0, 1), (2, 1)]);
+    }
 }
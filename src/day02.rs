@@ -1,103 +1,325 @@
-use crate::utils::read_some_to_vec;
-
-enum Move {
-    Rock,
-    Paper,
-    Scissors,
-}
+use std::fmt::{self, Display, Formatter};
+use std::fs::read_to_string;
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Outcome {
-    Win,
     Lose,
     Draw,
+    Win,
+}
+
+struct Scoring {
+    moves: Vec<i32>,
+    lose: i32,
+    draw: i32,
+    win: i32,
+}
+
+struct Game {
+    names: Vec<String>,
+    scoring: Scoring,
 }
 
-struct Round {
-    first: Move,
-    second: Move,
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Decoder {
+    Permutation(Vec<usize>),
+    OutcomeOffset,
 }
 
-impl Round {
-    fn outcome(&self) -> Outcome {
-        match (&self.first, &self.second) {
-            (Move::Rock, Move::Rock) => Outcome::Draw,
-            (Move::Rock, Move::Paper) => Outcome::Win,
-            (Move::Rock, Move::Scissors) => Outcome::Lose,
-            (Move::Paper, Move::Rock) => Outcome::Lose,
-            (Move::Paper, Move::Paper) => Outcome::Draw,
-            (Move::Paper, Move::Scissors) => Outcome::Win,
-            (Move::Scissors, Move::Rock) => Outcome::Win,
-            (Move::Scissors, Move::Paper) => Outcome::Lose,
-            (Move::Scissors, Move::Scissors) => Outcome::Draw,
+struct GuideError {
+    line: usize,
+    text: String,
+}
+
+impl Display for GuideError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "line {}: invalid round '{}'", self.line, self.text)
+    }
+}
+
+impl Scoring {
+    fn standard(n: usize) -> Scoring {
+        Scoring {
+            moves: (1..=n as i32).collect(),
+            lose: 0,
+            draw: 3,
+            win: 6,
         }
     }
+}
 
-    fn score(&self) -> i32 {
-        let outcome_score = match self.outcome() {
-            Outcome::Win => 6,
-            Outcome::Lose => 0,
-            Outcome::Draw => 3,
-        };
-        let move_score = match &self.second {
-            Move::Rock => 1,
-            Move::Paper => 2,
-            Move::Scissors => 3,
+impl Game {
+    fn new(names: &[&str], scoring: Scoring) -> Option<Game> {
+        let n = names.len();
+        if n < 3 || n.is_multiple_of(2) || scoring.moves.len() != n {
+            return None;
+        }
+        Some(Game {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            scoring,
+        })
+    }
+
+    fn rock_paper_scissors() -> Game {
+        Game::new(&["Rock", "Paper", "Scissors"], Scoring::standard(3)).unwrap()
+    }
+
+    fn size(&self) -> usize {
+        self.names.len()
+    }
+
+    fn outcome(&self, theirs: usize, mine: usize) -> Outcome {
+        let n = self.size();
+        match (mine + n - theirs) % n {
+            0 => Outcome::Draw,
+            d if d <= n / 2 => Outcome::Win,
+            _ => Outcome::Lose,
+        }
+    }
+
+    fn shift(&self, theirs: usize, offset: i64) -> usize {
+        (theirs as i64 + offset).rem_euclid(self.size() as i64) as usize
+    }
+
+    fn score(&self, theirs: usize, mine: usize) -> i32 {
+        let outcome = match self.outcome(theirs, mine) {
+            Outcome::Lose => self.scoring.lose,
+            Outcome::Draw => self.scoring.draw,
+            Outcome::Win => self.scoring.win,
         };
-        outcome_score + move_score
+        outcome + self.scoring.moves[mine]
     }
-}
 
-fn to_round_part1(line: &str) -> Option<Round> {
-    let parts: Vec<&str> = line.split(" ").collect();
-    let lhs = match parts[0] {
-        "A" => Some(Move::Rock),
-        "B" => Some(Move::Paper),
-        "C" => Some(Move::Scissors),
-        _ => None,
-    };
-    let rhs = match parts[1] {
-        "X" => Some(Move::Rock),
-        "Y" => Some(Move::Paper),
-        "Z" => Some(Move::Scissors),
-        _ => None,
-    };
+    fn decode(&self, decoder: &Decoder, theirs: usize, symbol: usize) -> usize {
+        match decoder {
+            Decoder::Permutation(moves) => moves[symbol],
+            Decoder::OutcomeOffset => self.shift(theirs, symbol as i64 - (self.size() / 2) as i64),
+        }
+    }
 
-    match (lhs, rhs) {
-        (Some(first), Some(second)) => Some(Round { first, second }),
-        _ => None,
+    fn total_score(&self, guide: &[(usize, usize)], decoder: &Decoder) -> i32 {
+        guide
+            .iter()
+            .map(|(theirs, symbol)| self.score(*theirs, self.decode(decoder, *theirs, *symbol)))
+            .sum()
     }
-}
 
-fn to_round_part2(line: &str) -> Option<Round> {
-    let parts: Vec<&str> = line.split(" ").collect();
-    let round = match (parts[0], parts[1]) {
-        ("A", "X") => Some((Move::Rock, Move::Scissors)),
-        ("A", "Y") => Some((Move::Rock, Move::Rock)),
-        ("A", "Z") => Some((Move::Rock, Move::Paper)),
-        ("B", "X") => Some((Move::Paper, Move::Rock)),
-        ("B", "Y") => Some((Move::Paper, Move::Paper)),
-        ("B", "Z") => Some((Move::Paper, Move::Scissors)),
-        ("C", "X") => Some((Move::Scissors, Move::Paper)),
-        ("C", "Y") => Some((Move::Scissors, Move::Scissors)),
-        ("C", "Z") => Some((Move::Scissors, Move::Rock)),
-        _ => None,
-    };
+    #[allow(dead_code)]
+    fn describe(&self, decoder: &Decoder) -> String {
+        let own = b'Z' + 1 - self.size() as u8;
+        match decoder {
+            Decoder::Permutation(moves) => moves
+                .iter()
+                .enumerate()
+                .map(|(symbol, mine)| {
+                    format!("{}={}", (own + symbol as u8) as char, self.names[*mine])
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+            Decoder::OutcomeOffset => "lose/draw/win".to_string(),
+        }
+    }
 
-    match round {
-        Some((first, second)) => Some(Round { first, second }),
-        _ => None,
+    #[allow(dead_code)]
+    fn best_interpretation(&self, guide: &[(usize, usize)]) -> (Decoder, i32) {
+        let n = self.size();
+        let mut weights = vec![vec![0; n]; n];
+        for (theirs, symbol) in guide {
+            for (mine, weight) in weights[*symbol].iter_mut().enumerate() {
+                *weight += self.score(*theirs, mine);
+            }
+        }
+        [
+            Decoder::OutcomeOffset,
+            Decoder::Permutation(best_assignment(&weights)),
+        ]
+        .into_iter()
+        .map(|decoder| {
+            let score = self.total_score(guide, &decoder);
+            (decoder, score)
+        })
+        .max_by_key(|(_, score)| *score)
+        .unwrap()
     }
 }
 
-fn total_score(rounds: Vec<Round>) -> i32 {
-    rounds.iter().map(|r| -> i32 { return r.score() }).sum()
+fn best_assignment(weights: &[Vec<i32>]) -> Vec<usize> {
+    let n = weights.len();
+    let (mut row_potential, mut col_potential) = (vec![0; n + 1], vec![0; n + 1]);
+    let (mut owner, mut way) = (vec![0; n + 1], vec![0; n + 1]);
+    for row in 1..=n {
+        owner[0] = row;
+        let mut col = 0;
+        let mut slack = vec![i32::MAX; n + 1];
+        let mut used = vec![false; n + 1];
+        while owner[col] != 0 {
+            used[col] = true;
+            let current = owner[col];
+            let (mut delta, mut next) = (i32::MAX, 0);
+            for j in 1..=n {
+                if used[j] {
+                    continue;
+                }
+                let cost = -weights[current - 1][j - 1] - row_potential[current] - col_potential[j];
+                if cost < slack[j] {
+                    slack[j] = cost;
+                    way[j] = col;
+                }
+                if slack[j] < delta {
+                    delta = slack[j];
+                    next = j;
+                }
+            }
+            for j in 0..=n {
+                if used[j] {
+                    row_potential[owner[j]] += delta;
+                    col_potential[j] -= delta;
+                } else {
+                    slack[j] -= delta;
+                }
+            }
+            col = next;
+        }
+        while col != 0 {
+            let previous = way[col];
+            owner[col] = owner[previous];
+            col = previous;
+        }
+    }
+    let mut moves = vec![0; n];
+    for (col, row) in owner.iter().enumerate().skip(1) {
+        moves[row - 1] = col - 1;
+    }
+    moves
+}
+
+fn to_guide(contents: &str, n: usize) -> Result<Vec<(usize, usize)>, GuideError> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let own = (b'Z' + 1 - n as u8) as char;
+            let symbols: Vec<char> = line.split_whitespace().flat_map(|s| s.chars()).collect();
+            match symbols[..] {
+                [theirs, mine] if theirs.is_ascii_uppercase() && mine >= own && mine <= 'Z' => {
+                    Some((theirs as usize - 'A' as usize, mine as usize - own as usize))
+                }
+                _ => None,
+            }
+            .filter(|(theirs, mine)| *theirs < n && *mine < n)
+            .ok_or(GuideError {
+                line: i + 1,
+                text: line.to_string(),
+            })
+        })
+        .collect()
 }
 
 pub fn run() {
-    let part1_rounds: Vec<Round> = read_some_to_vec("./data/day02.txt", to_round_part1);
-    let part2_rounds: Vec<Round> = read_some_to_vec("./data/day02.txt", to_round_part2);
+    let game = Game::rock_paper_scissors();
+    let contents = read_to_string("./data/day02.txt").unwrap_or_default();
 
     println!("== Day 02 ==");
-    println!("Part 1: {}", total_score(part1_rounds));
-    println!("Part 2: {}", total_score(part2_rounds));
+    let guide = match to_guide(&contents, game.size()) {
+        Ok(guide) => guide,
+        Err(error) => {
+            println!("Error: {}", error);
+            return;
+        }
+    };
+    let as_moves = Decoder::Permutation((0..game.size()).collect());
+    println!("Part 1: {}", game.total_score(&guide, &as_moves));
+    println!(
+        "Part 2: {}",
+        game.total_score(&guide, &Decoder::OutcomeOffset)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permutations(items: &mut Vec<usize>, k: usize, result: &mut Vec<Vec<usize>>) {
+        if k == items.len() {
+            result.push(items.clone());
+            return;
+        }
+        for i in k..items.len() {
+            items.swap(k, i);
+            permutations(items, k + 1, result);
+            items.swap(k, i);
+        }
+    }
+
+    #[test]
+    fn example() {
+        let game = Game::rock_paper_scissors();
+        let guide = to_guide("A Y\nB X\nC Z\n", game.size()).ok().unwrap();
+        let as_moves = Decoder::Permutation(vec![0, 1, 2]);
+        assert_eq!(game.total_score(&guide, &as_moves), 15);
+        assert_eq!(game.total_score(&guide, &Decoder::OutcomeOffset), 12);
+        let (decoder, score) = game.best_interpretation(&guide);
+        assert_eq!(score, 24);
+        assert_eq!(game.describe(&decoder), "X=Scissors, Y=Paper, Z=Rock");
+    }
+
+    #[test]
+    fn games_need_an_odd_number_of_moves() {
+        assert!(Game::new(&["A", "B", "C", "D"], Scoring::standard(4)).is_none());
+        assert!(Game::new(&["A", "B", "C"], Scoring::standard(5)).is_none());
+        let game = Game::new(
+            &["Rock", "Spock", "Paper", "Lizard", "Scissors"],
+            Scoring::standard(5),
+        );
+        let game = game.unwrap();
+        assert_eq!(game.outcome(0, 1), Outcome::Win);
+        assert_eq!(game.outcome(0, 2), Outcome::Win);
+        assert_eq!(game.outcome(0, 3), Outcome::Lose);
+        assert_eq!(game.outcome(4, 4), Outcome::Draw);
+        let guide = to_guide("A V\nE Z\n", game.size()).ok().unwrap();
+        assert_eq!(guide, [(0, 0), (4, 4)]);
+        assert_eq!(game.total_score(&guide, &Decoder::OutcomeOffset), 4 + 8);
+    }
+
+    #[test]
+    fn assignment_matches_exhaustive_search() {
+        let mut seed = 0x2545f4914f6cdd1du64;
+        for n in 1..=6 {
+            let weights: Vec<Vec<i32>> = (0..n)
+                .map(|_| {
+                    (0..n)
+                        .map(|_| {
+                            seed ^= seed << 13;
+                            seed ^= seed >> 7;
+                            seed ^= seed << 17;
+                            (seed % 100) as i32 - 30
+                        })
+                        .collect()
+                })
+                .collect();
+            let total = |moves: &[usize]| -> i32 {
+                moves
+                    .iter()
+                    .enumerate()
+                    .map(|(symbol, mine)| weights[symbol][*mine])
+                    .sum()
+            };
+            let mut all = vec![];
+            permutations(&mut (0..n).collect(), 0, &mut all);
+            let best = all.iter().map(|moves| total(moves)).max().unwrap();
+            let moves = best_assignment(&weights);
+            let mut sorted = moves.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, (0..n).collect::<Vec<usize>>());
+            assert_eq!(total(&moves), best);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_rounds() {
+        let error = to_guide("A X\nD X\n", 3).err().unwrap();
+        assert_eq!((error.line, error.text.as_str()), (2, "D X"));
+        assert!(to_guide("A W\n", 3).is_err());
+    }
 }
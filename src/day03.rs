@@ -1,68 +1,239 @@
 use crate::utils::read_to_vec;
-use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
 
-fn to_priority(item: char) -> i32 {
-    const LOWER: i32 = 'a' as i32;
-    const UPPER: i32 = 'A' as i32;
-    let priority: i32 = item as i32;
-    if item.is_lowercase() {
-        priority - LOWER + 1
-    } else {
-        priority - UPPER + 27
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+struct ItemSet(u64);
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum RucksackError {
+    InvalidItem(usize, char),
+    OddLength(usize),
+    NoCommonItem(usize),
+    AmbiguousCommonItem(usize, String),
+    IncompleteGroup(usize),
+    ZeroGroupSize,
+}
+
+impl Display for RucksackError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RucksackError::InvalidItem(line, item) => {
+                write!(f, "line {}: invalid item '{}'", line, item)
+            }
+            RucksackError::OddLength(line) => {
+                write!(f, "line {}: compartments have different sizes", line)
+            }
+            RucksackError::NoCommonItem(line) => write!(f, "line {}: no common item", line),
+            RucksackError::AmbiguousCommonItem(line, items) => {
+                write!(f, "line {}: several common items '{}'", line, items)
+            }
+            RucksackError::IncompleteGroup(line) => {
+                write!(f, "line {}: group is incomplete", line)
+            }
+            RucksackError::ZeroGroupSize => write!(f, "groups must hold at least one rucksack"),
+        }
     }
 }
 
-fn to_item_set(rucksack: &str) -> HashSet<char> {
-    rucksack.chars().into_iter().collect()
+fn to_priority(item: char) -> Option<u32> {
+    match item {
+        'a'..='z' => Some(item as u32 - 'a' as u32 + 1),
+        'A'..='Z' => Some(item as u32 - 'A' as u32 + 27),
+        _ => None,
+    }
 }
 
-fn common_items(lhs: String, rhs: &str) -> String {
-    match (lhs.as_str(), rhs) {
-        ("", value) => value.to_string(),
-        (a, b) => {
-            let first = to_item_set(a);
-            let second = to_item_set(b);
-            first.intersection(&second).collect()
-        }
+fn to_item(priority: u32) -> char {
+    match priority {
+        1..=26 => (b'a' + priority as u8 - 1) as char,
+        _ => (b'A' + priority as u8 - 27) as char,
     }
 }
 
-fn common_item(rucksacks: &[String]) -> char {
-    let common = rucksacks
-        .iter()
-        .fold("".to_string(), |acc, r| common_items(acc, r));
-    assert!(common.len() == 1);
-    common.chars().next().unwrap()
+impl ItemSet {
+    fn parse(line: usize, items: &str) -> Result<ItemSet, RucksackError> {
+        items
+            .chars()
+            .try_fold(ItemSet::default(), |set, item| match to_priority(item) {
+                Some(priority) => Ok(set.with(priority)),
+                None => Err(RucksackError::InvalidItem(line, item)),
+            })
+    }
+
+    fn all() -> ItemSet {
+        ItemSet(((1u64 << 52) - 1) << 1)
+    }
+
+    fn with(&self, priority: u32) -> ItemSet {
+        ItemSet(self.0 | 1 << priority)
+    }
+
+    fn contains(&self, priority: u32) -> bool {
+        self.0 & (1 << priority) != 0
+    }
+
+    fn intersection(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    #[allow(dead_code)]
+    fn union(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(self.0 | other.0)
+    }
+
+    fn len(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    fn priorities(&self) -> impl Iterator<Item = u32> + '_ {
+        (1..=52).filter(|priority| self.contains(*priority))
+    }
+
+    fn single(&self, line: usize) -> Result<u32, RucksackError> {
+        match self.len() {
+            0 => Err(RucksackError::NoCommonItem(line)),
+            1 => Ok(self.0.trailing_zeros()),
+            _ => Err(RucksackError::AmbiguousCommonItem(
+                line,
+                self.priorities().map(to_item).collect(),
+            )),
+        }
+    }
 }
 
-fn compartments(rucksack: &String) -> (String, String) {
-    let length = rucksack.len();
-    let first = rucksack[0..length / 2].to_string();
-    let second = rucksack[length / 2..length].to_string();
-    (first, second)
+fn common_priority(line: usize, sets: &[ItemSet]) -> Result<u32, RucksackError> {
+    sets.iter()
+        .fold(ItemSet::all(), |acc, set| acc.intersection(set))
+        .single(line)
 }
 
-fn part1(rucksacks: &Vec<String>) -> i32 {
+fn part1(rucksacks: &[String]) -> Result<u32, RucksackError> {
     rucksacks
         .iter()
-        .map(|r| compartments(r))
-        .map(|(first, second)| common_item(&[first, second]))
-        .map(|c| to_priority(c))
+        .enumerate()
+        .map(|(i, rucksack)| {
+            ItemSet::parse(i + 1, rucksack)?;
+            if rucksack.len() % 2 != 0 {
+                return Err(RucksackError::OddLength(i + 1));
+            }
+            let (first, second) = rucksack.split_at(rucksack.len() / 2);
+            let sets = [
+                ItemSet::parse(i + 1, first)?,
+                ItemSet::parse(i + 1, second)?,
+            ];
+            common_priority(i + 1, &sets)
+        })
         .sum()
 }
 
-fn part2(rucksacks: &Vec<String>) -> i32 {
+fn group_badges(rucksacks: &[String], group_size: usize) -> Result<u32, RucksackError> {
+    if group_size == 0 {
+        return Err(RucksackError::ZeroGroupSize);
+    }
     rucksacks
-        .chunks_exact(3)
-        .map(|r| common_item(r))
-        .map(|c| to_priority(c))
+        .chunks(group_size)
+        .enumerate()
+        .map(|(g, group)| {
+            let first_line = g * group_size + 1;
+            if group.len() < group_size {
+                return Err(RucksackError::IncompleteGroup(first_line));
+            }
+            let sets = group
+                .iter()
+                .enumerate()
+                .map(|(i, rucksack)| ItemSet::parse(first_line + i, rucksack))
+                .collect::<Result<Vec<ItemSet>, RucksackError>>()?;
+            common_priority(first_line, &sets)
+        })
         .sum()
 }
 
 pub fn run() {
-    let rucksacks = read_to_vec("data/day03.txt", |s| s.to_string());
+    let rucksacks = read_to_vec("data/day03.txt", |s| s.trim().to_string());
 
     println!("== Day 03 ==");
-    println!("Part 1: {}", part1(&rucksacks));
-    println!("Part 2: {}", part2(&rucksacks));
+    match part1(&rucksacks) {
+        Ok(total) => println!("Part 1: {}", total),
+        Err(error) => println!("Part 1 error: {}", error),
+    }
+    match group_badges(&rucksacks, 3) {
+        Ok(total) => println!("Part 2: {}", total),
+        Err(error) => println!("Part 2 error: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rucksacks(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn example() {
+        let rucksacks = rucksacks(&[
+            "vJrwpWtwJgWrhcsFMMfFFhFp",
+            "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+            "PmmdzqPrVvPwwTWBwg",
+            "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+            "ttgJtRGJQctTZtZT",
+            "CrZsJsPPZsGzwwsLwLmpwMDw",
+        ]);
+        assert_eq!(part1(&rucksacks), Ok(157));
+        assert_eq!(group_badges(&rucksacks, 3), Ok(70));
+        assert_eq!(
+            group_badges(&rucksacks, 2),
+            Err(RucksackError::AmbiguousCommonItem(1, "frsFM".to_string()))
+        );
+    }
+
+    #[test]
+    fn group_size_is_validated() {
+        let rucksacks = rucksacks(&["ab", "bc", "ca"]);
+        assert_eq!(
+            group_badges(&rucksacks, 0),
+            Err(RucksackError::ZeroGroupSize)
+        );
+        assert_eq!(
+            group_badges(&rucksacks, 2),
+            Err(RucksackError::IncompleteGroup(3))
+        );
+        assert_eq!(
+            group_badges(&rucksacks, 1),
+            Err(RucksackError::AmbiguousCommonItem(1, "ab".to_string()))
+        );
+        assert_eq!(group_badges(&[], 0), Err(RucksackError::ZeroGroupSize));
+        assert_eq!(
+            group_badges(&self::rucksacks(&["ab", "cd"]), 2),
+            Err(RucksackError::NoCommonItem(1))
+        );
+    }
+
+    #[test]
+    fn item_set_operations() {
+        let a = ItemSet::parse(1, "abcA").unwrap();
+        let b = ItemSet::parse(2, "cdAZ").unwrap();
+        let items = |set: ItemSet| set.priorities().map(to_item).collect::<String>();
+        assert_eq!(items(a.union(&b)), "abcdAZ");
+        assert_eq!(items(a.intersection(&b)), "cA");
+        assert_eq!(a.union(&b).len(), 6);
+        assert_eq!(a.union(&ItemSet::default()), a);
+        assert_eq!(a.union(&ItemSet::all()), ItemSet::all());
+        assert_eq!(ItemSet::all().len(), 52);
+        assert_eq!(ItemSet::default().len(), 0);
+    }
+
+    #[test]
+    fn invalid_rucksacks() {
+        assert_eq!(
+            part1(&rucksacks(&["aBc"])),
+            Err(RucksackError::OddLength(1))
+        );
+        assert_eq!(
+            part1(&rucksacks(&["aa", "a1"])),
+            Err(RucksackError::InvalidItem(2, '1'))
+        );
+        assert_eq!(to_item(to_priority('Q').unwrap()), 'Q');
+    }
 }
@@ -1,5 +1,6 @@
 use crate::utils::read_to_vec;
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct Range {
     start: i32,
     end: i32,
@@ -17,7 +18,7 @@ fn to_pair(line: &str) -> (Range, Range) {
     (to_range(parts[0]), to_range(parts[1]))
 }
 
-fn count_if(pairs: &Vec<(Range, Range)>, predicate: fn(&Range, &Range) -> bool) -> usize {
+fn count_if(pairs: &[(Range, Range)], predicate: fn(&Range, &Range) -> bool) -> usize {
     pairs.iter().filter(|(a, b)| predicate(a, b)).count()
 }
 
@@ -29,6 +30,87 @@ fn overlaps(lhs: &Range, rhs: &Range) -> bool {
     rhs.end >= lhs.start && rhs.start <= lhs.end
 }
 
+#[allow(dead_code)]
+fn assignments(pairs: &[(Range, Range)]) -> Vec<Range> {
+    pairs.iter().flat_map(|(a, b)| [*a, *b]).collect()
+}
+
+fn coverage(ranges: &[Range]) -> Vec<(Range, usize)> {
+    let mut events: Vec<(i32, i32)> = ranges
+        .iter()
+        .filter(|range| range.start <= range.end)
+        .flat_map(|range| [(range.start, 1), (range.end + 1, -1)])
+        .collect();
+    events.sort_unstable();
+
+    let mut segments = vec![];
+    let mut count = 0;
+    for (i, (position, delta)) in events.iter().enumerate() {
+        count += delta;
+        match events.get(i + 1) {
+            Some((next, _)) if next > position => segments.push((
+                Range {
+                    start: *position,
+                    end: next - 1,
+                },
+                count as usize,
+            )),
+            _ => {}
+        }
+    }
+    segments
+}
+
+#[allow(dead_code)]
+fn uncovered(ranges: &[Range]) -> Vec<Range> {
+    coverage(ranges)
+        .into_iter()
+        .filter(|(_, count)| *count == 0)
+        .map(|(range, _)| range)
+        .collect()
+}
+
+#[allow(dead_code)]
+fn max_overlap(ranges: &[Range]) -> Option<(Range, usize)> {
+    coverage(ranges)
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+}
+
+#[allow(dead_code)]
+fn minimal_cover(ranges: &[Range]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..ranges.len())
+        .filter(|i| ranges[*i].start <= ranges[*i].end)
+        .collect();
+    order.sort_by_key(|i| ranges[*i].start);
+
+    let mut chosen = vec![];
+    let mut covered = i32::MIN;
+    let mut next = 0;
+    loop {
+        let target = covered.saturating_add(1);
+        let mut best: Option<usize> = None;
+        while next < order.len() && ranges[order[next]].start <= target {
+            let candidate = order[next];
+            if best.is_none_or(|b| ranges[candidate].end > ranges[b].end) {
+                best = Some(candidate);
+            }
+            next += 1;
+        }
+        match best {
+            Some(elf) if ranges[elf].end >= target => {
+                chosen.push(elf);
+                covered = ranges[elf].end;
+            }
+            _ if next < order.len() => covered = ranges[order[next]].start - 1,
+            _ => break,
+        }
+    }
+    chosen.sort_unstable();
+    chosen
+}
+
 pub fn run() {
     let pairs = read_to_vec("data/day04.txt", to_pair);
 
@@ -36,3 +118,71 @@ pub fn run() {
     println!("Part 1: {}", count_if(&pairs, contains));
     println!("Part 2: {}", count_if(&pairs, overlaps));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: i32, end: i32) -> Range {
+        Range { start, end }
+    }
+
+    fn pairs(lines: &[&str]) -> Vec<(Range, Range)> {
+        lines.iter().map(|line| to_pair(line)).collect()
+    }
+
+    #[test]
+    fn example() {
+        let pairs = pairs(&[
+            "2-4,6-8", "2-3,4-5", "5-7,7-9", "2-8,3-7", "6-6,4-6", "2-6,4-8",
+        ]);
+        assert_eq!(count_if(&pairs, contains), 2);
+        assert_eq!(count_if(&pairs, overlaps), 4);
+
+        let ranges = assignments(&pairs);
+        let counts: Vec<usize> = coverage(&ranges)
+            .iter()
+            .flat_map(|(range, count)| (range.start..=range.end).map(move |_| *count))
+            .collect();
+        assert_eq!(counts, [4, 5, 7, 7, 8, 6, 4, 1]);
+        assert!(uncovered(&ranges).is_empty());
+        assert_eq!(max_overlap(&ranges), Some((range(6, 6), 8)));
+        assert_eq!(minimal_cover(&ranges), [5, 6]);
+    }
+
+    #[test]
+    fn overlapping_ranges_with_gaps() {
+        let ranges = [
+            range(1, 3),
+            range(2, 5),
+            range(4, 6),
+            range(10, 12),
+            range(11, 11),
+            range(8, 7),
+        ];
+        assert_eq!(
+            coverage(&ranges),
+            [
+                (range(1, 1), 1),
+                (range(2, 3), 2),
+                (range(4, 5), 2),
+                (range(6, 6), 1),
+                (range(7, 9), 0),
+                (range(10, 10), 1),
+                (range(11, 11), 2),
+                (range(12, 12), 1),
+            ]
+        );
+        assert_eq!(uncovered(&ranges), [range(7, 9)]);
+        assert_eq!(max_overlap(&ranges), Some((range(2, 3), 2)));
+        assert_eq!(minimal_cover(&ranges), [0, 2, 3]);
+    }
+
+    #[test]
+    fn empty_assignments() {
+        assert!(coverage(&[]).is_empty());
+        assert_eq!(max_overlap(&[]), None);
+        assert!(minimal_cover(&[range(3, 1)]).is_empty());
+        assert_eq!(minimal_cover(&[range(i32::MIN, i32::MAX)]), [0]);
+    }
+}
@@ -1,92 +1,341 @@
-use crate::utils::read_to_vec;
+use std::fmt::{self, Display, Formatter};
+use std::fs::read_to_string;
 
 struct Move {
+    line: usize,
     count: usize,
     from: usize,
     to: usize,
 }
 
-fn to_crates(line: &str) -> Vec<(usize, char)> {
-    line.char_indices()
-        .map(|(i, c)| match (i, c) {
-            (_, '[') => None,
-            (_, ']') => None,
-            (_, ' ') => None,
-            (_, c) => Some((i / 4, c)),
-        })
-        .filter_map(|a| a)
-        .collect()
+#[derive(Debug, Copy, Clone)]
+enum Crane {
+    OneAtATime,
+    AllAtOnce,
+    #[allow(dead_code)]
+    Capacity(usize),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum YardError {
+    MissingFooter,
+    ZeroCapacity,
+    InvalidLabel(String),
+    StrayCrate(usize, usize),
+    InvalidMove(usize, String),
+    NoSuchStack(usize, usize),
+    OverDraw {
+        line: usize,
+        stack: usize,
+        requested: usize,
+        available: usize,
+    },
 }
 
-fn to_move(line: &str) -> Move {
-    let parts: Vec<&str> = line.split(" ").collect();
-    let count: usize = parts[1].trim().parse().unwrap();
-    let from: usize = parts[3].trim().parse().unwrap();
-    let to: usize = parts[5].trim().parse().unwrap();
-    Move { count, from, to }
+#[derive(Clone)]
+struct CrateYard {
+    labels: Vec<usize>,
+    stacks: Vec<Vec<String>>,
 }
 
-fn parse() -> (Vec<String>, Vec<Move>) {
-    let mut stacks: Vec<String> = [0; 9].iter().map(|_| "".to_string()).collect();
-    let mut moves: Vec<Move> = vec![];
-    let lines = read_to_vec("data/day05.txt", |line| line.to_string());
-    for line in lines {
-        if line.starts_with(" 1") || line.len() == 0 {
-            continue;
+impl Display for YardError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            YardError::MissingFooter => write!(f, "no stack numbers below the crates"),
+            YardError::ZeroCapacity => write!(f, "crane capacity must be at least 1"),
+            YardError::InvalidLabel(label) => write!(f, "invalid stack number '{}'", label),
+            YardError::StrayCrate(line, column) => {
+                write!(
+                    f,
+                    "line {}: crate at column {} is not above a stack",
+                    line, column
+                )
+            }
+            YardError::InvalidMove(line, text) => {
+                write!(f, "line {}: invalid move '{}'", line, text)
+            }
+            YardError::NoSuchStack(line, label) => write!(f, "line {}: no stack {}", line, label),
+            YardError::OverDraw {
+                line,
+                stack,
+                requested,
+                available,
+            } => write!(
+                f,
+                "line {}: cannot take {} crates from stack {} holding {}",
+                line, requested, stack, available
+            ),
         }
+    }
+}
 
-        if line.starts_with("move") {
-            moves.push(to_move(&line))
-        } else {
-            let crates = to_crates(&line);
-            for (i, c) in crates {
-                stacks[i].insert(0, c)
+impl Crane {
+    fn capacity(&self) -> usize {
+        match self {
+            Crane::OneAtATime => 1,
+            Crane::AllAtOnce => usize::MAX,
+            Crane::Capacity(n) => *n,
+        }
+    }
+}
+
+fn spans(line: &str, open: char, close: char) -> Vec<(usize, usize, &str)> {
+    let mut spans = vec![];
+    let mut start = None;
+    for (i, c) in line.char_indices() {
+        match (c, start) {
+            (c, None) if c == open => start = Some(i),
+            (c, Some(s)) if c == close => {
+                spans.push((s, i, &line[s + open.len_utf8()..i]));
+                start = None;
             }
+            _ => {}
         }
     }
+    spans
+}
 
-    return (stacks, moves);
+fn to_labels(line: &str) -> Vec<(usize, usize, &str)> {
+    let mut labels = vec![];
+    let mut start = None;
+    for (i, c) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                labels.push((s, i - 1, &line[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    labels
 }
 
-fn message(stacks: &Vec<String>) -> String {
-    stacks
-        .iter()
-        .map(|s| match s.chars().last() {
-            Some(c) => c,
-            None => ' ',
-        })
-        .collect()
+fn to_move(line: usize, text: &str) -> Result<Move, YardError> {
+    let parts: Vec<&str> = text.split_whitespace().collect();
+    let numbers = match parts[..] {
+        ["move", count, "from", from, "to", to] => [count, from, to].map(|n| n.parse().ok()),
+        _ => [None; 3],
+    };
+    match numbers {
+        [Some(count), Some(from), Some(to)] => Ok(Move {
+            line,
+            count,
+            from,
+            to,
+        }),
+        _ => Err(YardError::InvalidMove(line, text.to_string())),
+    }
 }
 
-fn part1() -> String {
-    let (mut stacks, moves) = parse();
+impl CrateYard {
+    fn parse(drawing: &[&str]) -> Result<CrateYard, YardError> {
+        let (footer, rows) = drawing.split_last().ok_or(YardError::MissingFooter)?;
+        let labels = to_labels(footer);
+        if labels.is_empty() {
+            return Err(YardError::MissingFooter);
+        }
+        let numbers = labels
+            .iter()
+            .map(|(_, _, label)| {
+                label
+                    .parse()
+                    .map_err(|_| YardError::InvalidLabel(label.to_string()))
+            })
+            .collect::<Result<Vec<usize>, YardError>>()?;
 
-    for m in moves {
-        for _ in 0..m.count {
-            if let Some(c) = stacks[m.from - 1].pop() {
-                stacks[m.to - 1].push(c)
+        let mut stacks = vec![vec![]; labels.len()];
+        for (i, row) in rows.iter().enumerate().rev() {
+            for (start, end, id) in spans(row, '[', ']') {
+                let stack = labels
+                    .iter()
+                    .position(|(s, e, _)| *s <= end && start <= *e)
+                    .ok_or(YardError::StrayCrate(i + 1, start + 1))?;
+                stacks[stack].push(id.to_string());
             }
         }
+
+        Ok(CrateYard {
+            labels: numbers,
+            stacks,
+        })
     }
 
-    message(&stacks)
+    fn stack(&self, line: usize, label: usize) -> Result<usize, YardError> {
+        self.labels
+            .iter()
+            .position(|l| *l == label)
+            .ok_or(YardError::NoSuchStack(line, label))
+    }
+
+    fn apply(&mut self, m: &Move, crane: Crane) -> Result<(), YardError> {
+        let capacity = crane.capacity();
+        if capacity == 0 {
+            return Err(YardError::ZeroCapacity);
+        }
+        let (from, to) = (self.stack(m.line, m.from)?, self.stack(m.line, m.to)?);
+        let available = self.stacks[from].len();
+        if m.count > available {
+            return Err(YardError::OverDraw {
+                line: m.line,
+                stack: m.from,
+                requested: m.count,
+                available,
+            });
+        }
+        let mut remaining = m.count;
+        while remaining > 0 {
+            let lift = remaining.min(capacity);
+            let start = self.stacks[from].len() - lift;
+            let crates: Vec<String> = self.stacks[from].drain(start..).collect();
+            self.stacks[to].extend(crates);
+            remaining -= lift;
+        }
+        Ok(())
+    }
+
+    fn message(&self) -> String {
+        self.stacks
+            .iter()
+            .map(|s| match s.last() {
+                Some(id) => id.as_str(),
+                None => " ",
+            })
+            .collect()
+    }
 }
 
-fn part2() -> String {
-    let (mut stacks, moves) = parse();
+fn parse(contents: &str) -> Result<(CrateYard, Vec<Move>), YardError> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let split = lines
+        .iter()
+        .position(|line| line.trim().is_empty())
+        .unwrap_or(lines.len());
+    let yard = CrateYard::parse(&lines[..split])?;
+    let moves = lines
+        .iter()
+        .enumerate()
+        .skip(split)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| to_move(i + 1, line))
+        .collect::<Result<Vec<Move>, YardError>>()?;
+    Ok((yard, moves))
+}
 
+fn operate(yard: &CrateYard, moves: &[Move], crane: Crane) -> Result<String, YardError> {
+    let mut yard = yard.clone();
     for m in moves {
-        let end = stacks[m.from - 1].len();
-        let start = end - m.count;
-        let crates: String = stacks[m.from - 1].drain(start..end).collect();
-        stacks[m.to - 1] += crates.as_str()
+        yard.apply(m, crane)?;
     }
-
-    message(&stacks)
+    Ok(yard.message())
 }
 
 pub fn run() {
     println!("== Day 05 ==");
-    println!("Part 1: {}", part1());
-    println!("Part 2: {}", part2())
+    let contents = read_to_string("data/day05.txt").unwrap_or_default();
+    let (yard, moves) = match parse(&contents) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            println!("Error: {}", error);
+            return;
+        }
+    };
+    match operate(&yard, &moves, Crane::OneAtATime) {
+        Ok(message) => println!("Part 1: {}", message),
+        Err(error) => println!("Part 1 error: {}", error),
+    }
+    match operate(&yard, &moves, Crane::AllAtOnce) {
+        Ok(message) => println!("Part 2: {}", message),
+        Err(error) => println!("Part 2 error: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+";
+
+    #[test]
+    fn example() {
+        let (yard, moves) = parse(EXAMPLE).ok().unwrap();
+        assert_eq!(
+            operate(&yard, &moves, Crane::OneAtATime),
+            Ok("CMZ".to_string())
+        );
+        assert_eq!(
+            operate(&yard, &moves, Crane::AllAtOnce),
+            Ok("MCD".to_string())
+        );
+    }
+
+    #[test]
+    fn custom_capacity() {
+        let (yard, moves) = parse(EXAMPLE).ok().unwrap();
+        assert_eq!(
+            operate(&yard, &moves, Crane::Capacity(2)),
+            Ok("MCZ".to_string())
+        );
+        assert_eq!(
+            operate(&yard, &moves, Crane::Capacity(1)),
+            Ok("CMZ".to_string())
+        );
+        assert_eq!(
+            operate(&yard, &moves, Crane::Capacity(3)),
+            Ok("MCD".to_string())
+        );
+        assert_eq!(
+            operate(&yard, &moves, Crane::Capacity(0)),
+            Err(YardError::ZeroCapacity)
+        );
+    }
+
+    #[test]
+    fn invalid_drawings() {
+        assert_eq!(parse("").err(), Some(YardError::MissingFooter));
+        assert_eq!(
+            parse("[A]\n x\n").err(),
+            Some(YardError::InvalidLabel("x".to_string()))
+        );
+        assert_eq!(
+            parse("    [A]\n 1\n").err(),
+            Some(YardError::StrayCrate(1, 5))
+        );
+        assert_eq!(
+            parse("[A]\n 1\n\nmove one from 1 to 1\n").err(),
+            Some(YardError::InvalidMove(
+                4,
+                "move one from 1 to 1".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_moves() {
+        let (yard, _) = parse("[A]\n 1   2\n").ok().unwrap();
+        let moves = [to_move(4, "move 1 from 1 to 3").ok().unwrap()];
+        assert_eq!(
+            operate(&yard, &moves, Crane::OneAtATime),
+            Err(YardError::NoSuchStack(4, 3))
+        );
+        let moves = [to_move(5, "move 2 from 1 to 2").ok().unwrap()];
+        assert_eq!(
+            operate(&yard, &moves, Crane::AllAtOnce),
+            Err(YardError::OverDraw {
+                line: 5,
+                stack: 1,
+                requested: 2,
+                available: 1
+            })
+        );
+    }
 }
@@ -1,32 +1,173 @@
-use std::collections::HashSet;
-use std::fs::read_to_string;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
 
-fn find_start(line: &String, size: usize) -> Option<usize> {
-    for i in 0..line.len() - size {
-        let set: HashSet<char> = line[i..i + size].chars().collect();
-        if set.len() == size {
-            return Some(i + size);
+struct MarkerDetector {
+    window: Vec<u8>,
+    counts: [usize; 256],
+    distinct: usize,
+    position: usize,
+}
+
+impl MarkerDetector {
+    fn new(size: usize) -> MarkerDetector {
+        assert!(size > 0, "marker window must hold at least one character");
+        MarkerDetector {
+            window: vec![0; size],
+            counts: [0; 256],
+            distinct: 0,
+            position: 0,
         }
     }
 
-    None
+    fn push(&mut self, byte: u8) -> Option<usize> {
+        let size = self.window.len();
+        let slot = self.position % size;
+        if self.position >= size {
+            let old = self.window[slot] as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 0 {
+                self.distinct -= 1;
+            }
+        }
+        self.window[slot] = byte;
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 1 {
+            self.distinct += 1;
+        }
+        self.position += 1;
+
+        match self.distinct == size {
+            true => Some(self.position),
+            false => None,
+        }
+    }
 }
 
-pub fn run() {
-    let buffer = match read_to_string("data/day06.txt") {
-        Ok(contents) => contents.trim().to_string(),
-        _ => "".to_string(),
-    };
+fn markers<I: IntoIterator<Item = u8>>(bytes: I, size: usize) -> impl Iterator<Item = usize> {
+    let mut detector = MarkerDetector::new(size);
+    bytes
+        .into_iter()
+        .filter(|byte| !matches!(byte, b'\n' | b'\r'))
+        .filter_map(move |byte| detector.push(byte))
+}
 
+fn scan<R: Read>(
+    reader: R,
+    size: usize,
+    mut on_marker: impl FnMut(usize) -> bool,
+) -> io::Result<()> {
+    let mut error = None;
+    let bytes = BufReader::with_capacity(1 << 16, reader)
+        .bytes()
+        .map_while(|byte| byte.map_err(|e| error = Some(e)).ok());
+    for position in markers(bytes, size) {
+        if !on_marker(position) {
+            break;
+        }
+    }
+    error.map_or(Ok(()), Err)
+}
+
+fn find_start(filename: &str, size: usize) -> io::Result<Option<usize>> {
+    let mut start = None;
+    scan(File::open(filename)?, size, |position| {
+        start = Some(position);
+        false
+    })?;
+    Ok(start)
+}
+
+pub fn run() {
     println!("== Day 06 ==");
 
-    match find_start(&buffer, 4) {
-        Some(index) => println!("Part 1: {}", index),
-        None => println!("Part 1 error"),
+    match find_start("data/day06.txt", 4) {
+        Ok(Some(index)) => println!("Part 1: {}", index),
+        Ok(None) => println!("Part 1 error: no marker found"),
+        Err(error) => println!("Part 1 error: {}", error),
+    }
+
+    match find_start("data/day06.txt", 14) {
+        Ok(Some(index)) => println!("Part 2: {}", index),
+        Ok(None) => println!("Part 2 error: no marker found"),
+        Err(error) => println!("Part 2 error: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markers_of(input: &[u8], size: usize) -> Vec<usize> {
+        let mut found = vec![];
+        scan(input, size, |position| {
+            found.push(position);
+            true
+        })
+        .unwrap();
+        found
+    }
+
+    #[test]
+    fn examples() {
+        let examples = [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+        ];
+        for (input, packet, message) in examples {
+            assert_eq!(markers_of(input.as_bytes(), 4).first(), Some(&packet));
+            assert_eq!(markers_of(input.as_bytes(), 14).first(), Some(&message));
+        }
+    }
+
+    #[test]
+    fn reports_every_marker_and_skips_line_breaks() {
+        assert_eq!(markers_of(b"aab\r\nca", 3), [4, 5]);
+        assert_eq!(markers_of(b"aaaa", 2), []);
+        assert_eq!(markers_of(b"", 1), []);
+    }
+
+    #[test]
+    fn iterator_and_reader_agree() {
+        let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb\nzcfzfwzzqfrljwzlrfnpqdbhtmscgvjw";
+        for size in [1, 4, 14, 30] {
+            let found: Vec<usize> = markers(input.bytes(), size).collect();
+            assert_eq!(found, markers_of(input.as_bytes(), size));
+        }
+        let mut stream = markers((0..=255u8).cycle(), 200);
+        assert_eq!(stream.next(), Some(200));
+        assert_eq!(stream.nth(1000), Some(1201));
+    }
+
+    #[test]
+    fn read_errors_are_reported() {
+        struct Failing(bool);
+        impl Read for Failing {
+            fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+                match std::mem::replace(&mut self.0, true) {
+                    false => {
+                        buffer[..3].copy_from_slice(b"abc");
+                        Ok(3)
+                    }
+                    true => Err(io::Error::other("device unplugged")),
+                }
+            }
+        }
+        let mut found = vec![];
+        let result = scan(Failing(false), 2, |position| {
+            found.push(position);
+            true
+        });
+        assert_eq!(found, [2, 3]);
+        assert_eq!(result.err().unwrap().to_string(), "device unplugged");
     }
 
-    match find_start(&buffer, 14) {
-        Some(index) => println!("Part 2: {}", index),
-        None => println!("Part 2 error"),
+    #[test]
+    fn markers_span_buffer_boundaries() {
+        let mut input = vec![b'a'; (1 << 16) - 2];
+        input.extend(b"bcd");
+        assert_eq!(markers_of(&input, 4), [(1 << 16) + 1]);
     }
 }
@@ -1,113 +1,652 @@
-use std::collections::HashMap;
-
 use crate::utils::read_to_vec;
+use vfs::Vfs;
 
-enum Item {
-    Directory(String, Vec<String>),
-    File(String, i64),
-}
+mod vfs {
+    use std::cmp::Reverse;
+    use std::collections::BTreeMap;
+    use std::fmt::{self, Display, Formatter};
 
-impl Item {
-    fn size(&self, items: &HashMap<String, Item>) -> i64 {
-        match self {
-            Item::File(_, length) => *length,
-            Item::Directory(_, children) => children.iter().map(|i| items[i].size(items)).sum(),
-        }
+    pub enum Item {
+        Directory(BTreeMap<String, usize>),
+        File(i64),
     }
-}
 
-fn join_path(dir: &String, name: &str) -> String {
-    match dir.as_str() {
-        "" => name.to_string(),
-        prefix if prefix.ends_with("/") => [prefix, name].join(""),
-        prefix => [prefix, name].join("/"),
+    pub struct Entry {
+        pub name: String,
+        pub parent: usize,
+        pub item: Item,
     }
-}
 
-fn dir_name(dir: &String) -> String {
-    match dir.rfind('/') {
-        Some(0) => "/".to_string(),
-        Some(index) => dir[0..index].to_string(),
-        None => "".to_string(),
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    pub enum VfsErrorKind {
+        UnknownCommand(String),
+        OutputOutsideLs(String),
+        NotADirectory(String),
+        KindConflict(String),
+        SizeConflict(String, i64, i64),
+        InvalidName(String),
+    }
+
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    pub struct VfsError {
+        pub line: usize,
+        pub kind: VfsErrorKind,
     }
-}
 
-fn process(lines: Vec<String>) -> HashMap<String, Item> {
-    let mut items = HashMap::new();
-    let mut dir = "".to_string();
-    let mut files: Vec<String> = vec![];
-    for line in lines {
-        let parts: Vec<&str> = line.trim().split(" ").collect();
-        match parts[..] {
-            ["$", "cd", ".."] => {
-                if files.len() > 0 {
-                    items.insert(dir.clone(), Item::Directory(dir.clone(), files.clone()));
-                    files.clear();
+    pub struct Vfs {
+        entries: Vec<Entry>,
+    }
+
+    #[allow(dead_code)]
+    pub struct Plan {
+        pub entries: Vec<usize>,
+        pub total: i64,
+    }
+
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    pub enum CleanupError {
+        NotEnoughSpace(i64),
+        TooLarge(i64),
+    }
+
+    pub const ROOT: usize = 0;
+
+    impl Display for VfsError {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            write!(f, "line {}: ", self.line)?;
+            match &self.kind {
+                VfsErrorKind::UnknownCommand(command) => write!(f, "unknown command '{}'", command),
+                VfsErrorKind::OutputOutsideLs(output) => {
+                    write!(f, "output '{}' without a preceding ls", output)
                 }
-                dir = dir_name(&dir);
-            }
-            ["$", "cd", dir_name] => {
-                if files.len() > 0 {
-                    items.insert(dir.clone(), Item::Directory(dir.clone(), files.clone()));
-                    files.clear();
+                VfsErrorKind::NotADirectory(path) => write!(f, "{} is not a directory", path),
+                VfsErrorKind::KindConflict(path) => {
+                    write!(f, "{} listed as both a file and a directory", path)
                 }
-                dir = join_path(&dir, dir_name)
+                VfsErrorKind::SizeConflict(path, old, new) => {
+                    write!(f, "{} listed with sizes {} and {}", path, old, new)
+                }
+                VfsErrorKind::InvalidName(name) => write!(f, "invalid name '{}'", name),
             }
-            ["$", "ls"] => files.clear(),
-            ["dir", name] => {
-                let key = join_path(&dir, name);
-                items.insert(key.clone(), Item::Directory(key.clone(), vec![]));
-                files.push(key);
+        }
+    }
+
+    impl Display for CleanupError {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match self {
+                CleanupError::NotEnoughSpace(target) => {
+                    write!(f, "no directory frees {} bytes", target)
+                }
+                CleanupError::TooLarge(bound) => {
+                    write!(f, "cleanup bound {} is too large to plan exactly", bound)
+                }
             }
-            [digits, name] => {
-                let key = join_path(&dir, name);
-                let length: i64 = digits.parse().unwrap();
-                items.insert(key.clone(), Item::File(key.clone(), length));
-                files.push(key);
+        }
+    }
+
+    const MAX_BOUND: i64 = 1 << 24;
+
+    fn min_subset_sum(sizes: &[i64], target: i64, bound: i64) -> Option<Vec<usize>> {
+        let words = bound as usize / 64 + 1;
+        let mut reachable = vec![0u64; words];
+        let mut first = vec![u32::MAX; bound as usize + 1];
+        reachable[0] = 1;
+        for (i, size) in sizes.iter().enumerate() {
+            let (shift, offset) = (*size as usize / 64, *size as usize % 64);
+            for w in (shift..words).rev() {
+                let mut shifted = reachable[w - shift] << offset;
+                if offset > 0 && w > shift {
+                    shifted |= reachable[w - shift - 1] >> (64 - offset);
+                }
+                let mut added = shifted & !reachable[w];
+                reachable[w] |= added;
+                while added != 0 {
+                    if let Some(sum) = first.get_mut(w * 64 + added.trailing_zeros() as usize) {
+                        *sum = i as u32;
+                    }
+                    added &= added - 1;
+                }
             }
-            _ => panic!("Error"),
         }
+
+        let mut sum = (target.max(0)..=bound)
+            .find(|s| reachable[*s as usize / 64] & (1 << (s % 64)) != 0)?
+            as usize;
+        let mut chosen = vec![];
+        while sum > 0 {
+            let item = first[sum] as usize;
+            chosen.push(item);
+            sum -= sizes[item] as usize;
+        }
+        Some(chosen)
     }
 
-    if files.len() > 0 {
-        items.insert(dir.clone(), Item::Directory(dir.clone(), files.clone()));
+    impl Item {
+        pub fn size(&self, vfs: &Vfs) -> i64 {
+            match self {
+                Item::File(length) => *length,
+                Item::Directory(children) => children
+                    .values()
+                    .map(|id| vfs.entries[*id].item.size(vfs))
+                    .sum(),
+            }
+        }
+
+        pub fn is_dir(&self) -> bool {
+            matches!(self, Item::Directory(_))
+        }
     }
 
-    items
+    impl Vfs {
+        pub fn new() -> Vfs {
+            Vfs {
+                entries: vec![Entry {
+                    name: "/".to_string(),
+                    parent: ROOT,
+                    item: Item::Directory(BTreeMap::new()),
+                }],
+            }
+        }
+
+        pub fn from_transcript(lines: &[String]) -> Result<Vfs, VfsError> {
+            let mut vfs = Vfs::new();
+            let mut cwd = ROOT;
+            let mut listing = false;
+            for (i, line) in lines.iter().enumerate() {
+                let error = |kind| VfsError { line: i + 1, kind };
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                match parts[..] {
+                    [] => {}
+                    ["$", "cd", path] => {
+                        listing = false;
+                        cwd = vfs.make_dirs(cwd, path).map_err(error)?;
+                    }
+                    ["$", "ls"] => listing = true,
+                    ["$", ..] => return Err(error(VfsErrorKind::UnknownCommand(line.clone()))),
+                    _ if !listing => {
+                        return Err(error(VfsErrorKind::OutputOutsideLs(line.clone())))
+                    }
+                    ["dir", name] => {
+                        vfs.insert(cwd, name, Item::Directory(BTreeMap::new()))
+                            .map_err(error)?;
+                    }
+                    [size, name] => match size.parse() {
+                        Ok(length) => {
+                            vfs.insert(cwd, name, Item::File(length)).map_err(error)?;
+                        }
+                        Err(_) => return Err(error(VfsErrorKind::OutputOutsideLs(line.clone()))),
+                    },
+                    _ => return Err(error(VfsErrorKind::OutputOutsideLs(line.clone()))),
+                }
+            }
+            Ok(vfs)
+        }
+
+        fn children(&self, dir: usize) -> Result<&BTreeMap<String, usize>, VfsErrorKind> {
+            match &self.entries[dir].item {
+                Item::Directory(children) => Ok(children),
+                Item::File(_) => Err(VfsErrorKind::NotADirectory(self.path(dir))),
+            }
+        }
+
+        fn insert(&mut self, dir: usize, name: &str, item: Item) -> Result<usize, VfsErrorKind> {
+            if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+                return Err(VfsErrorKind::InvalidName(name.to_string()));
+            }
+            if let Some(&id) = self.children(dir)?.get(name) {
+                return match (&self.entries[id].item, &item) {
+                    (Item::Directory(_), Item::Directory(_)) => Ok(id),
+                    (Item::File(old), Item::File(new)) if old == new => Ok(id),
+                    (Item::File(old), Item::File(new)) => {
+                        Err(VfsErrorKind::SizeConflict(self.path(id), *old, *new))
+                    }
+                    _ => Err(VfsErrorKind::KindConflict(self.path(id))),
+                };
+            }
+            let id = self.entries.len();
+            self.entries.push(Entry {
+                name: name.to_string(),
+                parent: dir,
+                item,
+            });
+            if let Item::Directory(children) = &mut self.entries[dir].item {
+                children.insert(name.to_string(), id);
+            }
+            Ok(id)
+        }
+
+        fn make_dirs(&mut self, cwd: usize, path: &str) -> Result<usize, VfsErrorKind> {
+            let mut dir = if path.starts_with('/') { ROOT } else { cwd };
+            for component in path.split('/').filter(|c| !c.is_empty()) {
+                dir = match component {
+                    "." => dir,
+                    ".." => self.entries[dir].parent,
+                    name => match self.children(dir)?.get(name) {
+                        Some(&id) => id,
+                        None => self.insert(dir, name, Item::Directory(BTreeMap::new()))?,
+                    },
+                };
+                self.children(dir)?;
+            }
+            Ok(dir)
+        }
+
+        #[allow(dead_code)]
+        pub fn resolve(&self, cwd: usize, path: &str) -> Option<usize> {
+            let mut node = if path.starts_with('/') { ROOT } else { cwd };
+            for component in path.split('/').filter(|c| !c.is_empty()) {
+                node = match component {
+                    "." => node,
+                    ".." => self.entries[node].parent,
+                    name => *self.children(node).ok()?.get(name)?,
+                };
+            }
+            Some(node)
+        }
+
+        pub fn entry(&self, id: usize) -> &Entry {
+            &self.entries[id]
+        }
+
+        pub fn path(&self, id: usize) -> String {
+            let mut names = vec![];
+            let mut node = id;
+            while node != ROOT {
+                names.push(self.entries[node].name.as_str());
+                node = self.entries[node].parent;
+            }
+            names.reverse();
+            format!("/{}", names.join("/"))
+        }
+
+        pub fn sizes(&self) -> Vec<i64> {
+            let mut sizes: Vec<i64> = self
+                .entries
+                .iter()
+                .map(|entry| match entry.item {
+                    Item::File(length) => length,
+                    Item::Directory(_) => 0,
+                })
+                .collect();
+            for id in (1..self.entries.len()).rev() {
+                sizes[self.entries[id].parent] += sizes[id];
+            }
+            sizes
+        }
+
+        pub fn directories(&self) -> impl Iterator<Item = usize> + '_ {
+            (0..self.entries.len()).filter(|id| self.entries[*id].item.is_dir())
+        }
+
+        #[allow(dead_code)]
+        pub fn du(&self) -> Vec<(String, i64)> {
+            let sizes = self.sizes();
+            self.directories()
+                .map(|id| (self.path(id), sizes[id]))
+                .collect()
+        }
+
+        pub fn directories_at_most(&self, threshold: i64) -> Vec<(usize, i64)> {
+            let sizes = self.sizes();
+            let mut found: Vec<(usize, i64)> = self
+                .directories()
+                .filter(|id| sizes[*id] <= threshold)
+                .map(|id| (id, sizes[id]))
+                .collect();
+            found.sort_by_key(|(_, size)| *size);
+            found
+        }
+
+        pub fn directories_at_least(&self, threshold: i64) -> Vec<(usize, i64)> {
+            let sizes = self.sizes();
+            let mut found: Vec<(usize, i64)> = self
+                .directories()
+                .filter(|id| sizes[*id] >= threshold)
+                .map(|id| (id, sizes[id]))
+                .collect();
+            found.sort_by_key(|(_, size)| *size);
+            found
+        }
+
+        pub fn smallest_at_least(&self, threshold: i64) -> Option<(usize, i64)> {
+            self.directories_at_least(threshold).first().copied()
+        }
+
+        #[allow(dead_code)]
+        pub fn largest_at_most(&self, threshold: i64) -> Option<(usize, i64)> {
+            self.directories_at_most(threshold).last().copied()
+        }
+
+        #[allow(dead_code)]
+        pub fn tree(&self) -> String {
+            let mut lines = vec![];
+            let mut stack = vec![(ROOT, 0)];
+            while let Some((id, depth)) = stack.pop() {
+                let entry = &self.entries[id];
+                let indent = "  ".repeat(depth);
+                match &entry.item {
+                    Item::File(length) => lines.push(format!(
+                        "{}- {} (file, size={})",
+                        indent, entry.name, length
+                    )),
+                    Item::Directory(children) => {
+                        lines.push(format!("{}- {} (dir)", indent, entry.name));
+                        stack.extend(children.values().rev().map(|child| (*child, depth + 1)));
+                    }
+                }
+            }
+            lines.join("\n")
+        }
+
+        #[allow(dead_code)]
+        pub fn top_consumers(&self, n: usize) -> Vec<(usize, i64)> {
+            let sizes = self.sizes();
+            let mut consumers: Vec<(usize, i64)> =
+                (1..self.entries.len()).map(|id| (id, sizes[id])).collect();
+            consumers.sort_by_key(|(id, size)| (Reverse(*size), *id));
+            consumers.truncate(n);
+            consumers
+        }
+
+        #[allow(dead_code)]
+        pub fn plan_cleanup(&self, target: i64) -> Result<Plan, CleanupError> {
+            if target <= 0 {
+                return Ok(Plan {
+                    entries: vec![],
+                    total: 0,
+                });
+            }
+            let sizes = self.sizes();
+            let bound = match sizes.iter().filter(|size| **size >= target).min() {
+                Some(&size) if size > MAX_BOUND => return Err(CleanupError::TooLarge(size)),
+                Some(&size) => size,
+                None => return Err(CleanupError::NotEnoughSpace(target)),
+            };
+            let files: Vec<usize> = (0..self.entries.len())
+                .filter(|id| !self.entries[*id].item.is_dir() && (1..=bound).contains(&sizes[*id]))
+                .collect();
+            let file_sizes: Vec<i64> = files.iter().map(|id| sizes[*id]).collect();
+            let chosen = min_subset_sum(&file_sizes, target, bound)
+                .ok_or(CleanupError::NotEnoughSpace(target))?;
+
+            let mut selected = vec![false; self.entries.len()];
+            for item in chosen {
+                selected[files[item]] = true;
+            }
+            let mut total_files = vec![0; self.entries.len()];
+            let mut selected_files = vec![0; self.entries.len()];
+            for id in (1..self.entries.len()).rev() {
+                if !self.entries[id].item.is_dir() {
+                    total_files[id] = 1;
+                    selected_files[id] = (selected[id] || sizes[id] == 0) as usize;
+                }
+                let parent = self.entries[id].parent;
+                total_files[parent] += total_files[id];
+                selected_files[parent] += selected_files[id];
+            }
+
+            let mut entries = vec![];
+            let mut stack = vec![ROOT];
+            while let Some(id) = stack.pop() {
+                match &self.entries[id].item {
+                    Item::Directory(_)
+                        if id != ROOT
+                            && total_files[id] > 0
+                            && selected_files[id] == total_files[id] =>
+                    {
+                        entries.push(id)
+                    }
+                    Item::Directory(children) => stack.extend(children.values()),
+                    Item::File(_) if selected[id] => entries.push(id),
+                    Item::File(_) => {}
+                }
+            }
+            entries.sort_unstable();
+            let total = entries.iter().map(|id| sizes[*id]).sum();
+            Ok(Plan { entries, total })
+        }
+    }
 }
 
-fn part1(items: &HashMap<String, Item>) -> i64 {
-    items
+fn part1(vfs: &Vfs) -> i64 {
+    vfs.directories_at_most(100000)
         .iter()
-        .map(|(_, item)| match item {
-            Item::Directory(_, _) => Some(item.size(items)),
-            Item::File(_, _) => None,
-        })
-        .filter_map(|d| d)
-        .filter(|s| *s <= 100000)
+        .map(|(_, size)| size)
         .sum()
 }
 
-fn part2(items: &HashMap<String, Item>) -> i64 {
-    let free_space = 70000000 - items["/"].size(items);
-    let target = 30000000 - free_space;
-    items
-        .iter()
-        .map(|(_, item)| match item {
-            Item::Directory(_, _) => Some(item.size(items)),
-            Item::File(_, _) => None,
-        })
-        .filter_map(|d| d)
-        .filter(|s| *s >= target)
-        .min()
-        .unwrap()
+fn space_needed(vfs: &Vfs) -> i64 {
+    let free_space = 70000000 - vfs.entry(vfs::ROOT).item.size(vfs);
+    30000000 - free_space
+}
+
+fn part2(vfs: &Vfs) -> Option<i64> {
+    vfs.smallest_at_least(space_needed(vfs))
+        .map(|(_, size)| size)
 }
 
 pub fn run() {
     let lines: Vec<String> = read_to_vec("data/day07.txt", |a| a.to_string());
-    let items = process(lines);
 
     println!("== Day 07 ==");
-    println!("Part 1: {}", part1(&items));
-    println!("Part 2: {}", part2(&items))
+    let vfs = match Vfs::from_transcript(&lines) {
+        Ok(vfs) => vfs,
+        Err(error) => {
+            println!("Error: {}", error);
+            return;
+        }
+    };
+    println!("Part 1: {}", part1(&vfs));
+    match part2(&vfs) {
+        Some(size) => println!("Part 2: {}", size),
+        None => println!("Part 2 error: no directory is large enough"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::vfs::{CleanupError, VfsError, VfsErrorKind};
+    use super::*;
+
+    const EXAMPLE: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    fn load(transcript: &str) -> Result<Vfs, VfsError> {
+        let lines: Vec<String> = transcript.lines().map(|line| line.to_string()).collect();
+        Vfs::from_transcript(&lines)
+    }
+
+    fn paths(vfs: &Vfs, ids: &[usize]) -> Vec<String> {
+        ids.iter().map(|id| vfs.path(*id)).collect()
+    }
+
+    #[test]
+    fn example() {
+        let vfs = load(EXAMPLE).unwrap();
+        assert_eq!(part1(&vfs), 95437);
+        assert_eq!(part2(&vfs), Some(24933642));
+        let consumers: Vec<usize> = vfs.top_consumers(3).iter().map(|(id, _)| *id).collect();
+        assert_eq!(paths(&vfs, &consumers), ["/d", "/b.txt", "/c.dat"]);
+    }
+
+    #[test]
+    fn transcript_errors() {
+        let error = load("$ cd /\n12 a\n").err().unwrap();
+        assert_eq!(error.line, 2);
+        assert_eq!(
+            error.kind,
+            VfsErrorKind::OutputOutsideLs("12 a".to_string())
+        );
+        let error = load("$ ls\n12 a\n13 a\n").err().unwrap();
+        assert_eq!(
+            error.kind,
+            VfsErrorKind::SizeConflict("/a".to_string(), 12, 13)
+        );
+    }
+
+    #[test]
+    fn tree_rendering() {
+        let vfs = load(EXAMPLE).unwrap();
+        let expected = "- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)";
+        assert_eq!(vfs.tree(), expected);
+    }
+
+    #[test]
+    fn du_and_threshold_queries() {
+        let vfs = load(EXAMPLE).unwrap();
+        let du = vfs.du();
+        let expected = [
+            ("/", 48381165),
+            ("/a", 94853),
+            ("/a/e", 584),
+            ("/d", 24933642),
+        ];
+        assert_eq!(du.len(), expected.len());
+        for (path, size) in expected {
+            assert!(du.contains(&(path.to_string(), size)));
+        }
+        let largest = vfs.largest_at_most(100000).unwrap();
+        assert_eq!((vfs.path(largest.0), largest.1), ("/a".to_string(), 94853));
+        let smallest = vfs.smallest_at_least(100000).unwrap();
+        assert_eq!(vfs.path(smallest.0), "/d");
+        assert!(vfs.largest_at_most(100).is_none());
+        assert!(vfs.smallest_at_least(50000000).is_none());
+    }
+
+    #[test]
+    fn path_resolution() {
+        let vfs = load("$ cd /a/b\n$ ls\n1 f\n$ cd /\n$ cd c\n$ cd ../a\n$ ls\n2 g\n").unwrap();
+        let b = vfs.resolve(vfs::ROOT, "a/b").unwrap();
+        assert_eq!(vfs.path(b), "/a/b");
+        assert_eq!(
+            vfs.resolve(b, "f").map(|id| vfs.path(id)),
+            Some("/a/b/f".to_string())
+        );
+        assert_eq!(
+            vfs.resolve(b, "../g").map(|id| vfs.path(id)),
+            Some("/a/g".to_string())
+        );
+        assert_eq!(vfs.resolve(b, "/c/./.."), Some(vfs::ROOT));
+        assert_eq!(vfs.resolve(b, ".."), vfs.resolve(vfs::ROOT, "/a/"));
+        assert_eq!(vfs.resolve(b, "missing"), None);
+        assert_eq!(vfs.resolve(b, "f/x"), None);
+        assert_eq!(vfs.sizes()[vfs::ROOT], 3);
+    }
+
+    #[test]
+    fn cleanup_picks_the_smallest_sufficient_subset() {
+        let vfs = load(EXAMPLE).unwrap();
+        let plan = vfs.plan_cleanup(8000000).unwrap();
+        assert_eq!(plan.total, 8033020);
+        assert_eq!(paths(&vfs, &plan.entries), ["/d/d.log"]);
+        let plan = vfs.plan_cleanup(12000000).unwrap();
+        assert_eq!(plan.total, 12093194);
+        assert_eq!(paths(&vfs, &plan.entries), ["/d/j", "/d/d.log"]);
+        assert_eq!(
+            vfs.plan_cleanup(50000000).err(),
+            Some(CleanupError::NotEnoughSpace(50000000))
+        );
+    }
+
+    #[test]
+    fn cleanup_collapses_directories_with_zero_size_files() {
+        let vfs = load("$ ls\ndir a\n7 b\n$ cd a\n$ ls\n5 c\n0 empty\n").unwrap();
+        let plan = vfs.plan_cleanup(5).unwrap();
+        assert_eq!(plan.total, 5);
+        assert_eq!(paths(&vfs, &plan.entries), ["/a"]);
+        let vfs = load("$ ls\n0 a\n0 b\n3 c\n").unwrap();
+        let plan = vfs.plan_cleanup(2).unwrap();
+        assert_eq!(paths(&vfs, &plan.entries), ["/c"]);
+        assert_eq!(plan.total, 3);
+    }
+
+    #[test]
+    fn cleanup_matches_exhaustive_search() {
+        let sizes = [
+            913, 4021, 77, 1500, 2999, 3000, 640, 1234, 5555, 808, 2048, 4096,
+        ];
+        let mut transcript = "$ cd /\n$ ls\ndir x\n".to_string();
+        for (i, size) in sizes.iter().enumerate() {
+            if i == sizes.len() / 2 {
+                transcript.push_str("$ cd x\n$ ls\n");
+            }
+            transcript.push_str(&format!("{} f{}\n", size, i));
+        }
+        let vfs = load(&transcript).unwrap();
+        for target in [1, 500, 5000, 9999, 12345, 20000, 26891] {
+            let best = (0u32..1 << sizes.len())
+                .map(|mask| {
+                    (0..sizes.len())
+                        .filter(|i| mask & (1 << i) != 0)
+                        .map(|i| sizes[i])
+                        .sum::<i64>()
+                })
+                .filter(|total| *total >= target)
+                .min();
+            let plan = vfs.plan_cleanup(target).unwrap();
+            assert_eq!(Some(plan.total), best);
+            let sizes = vfs.sizes();
+            assert_eq!(
+                plan.entries.iter().map(|id| sizes[*id]).sum::<i64>(),
+                plan.total
+            );
+        }
+    }
+
+    #[test]
+    fn cleanup_reports_bounds_too_large_to_plan() {
+        let vfs = load("$ ls\n40000000 big\n").unwrap();
+        assert_eq!(
+            vfs.plan_cleanup(30000000).err(),
+            Some(CleanupError::TooLarge(40000000))
+        );
+        assert_eq!(
+            vfs.plan_cleanup(40000001).err(),
+            Some(CleanupError::NotEnoughSpace(40000001))
+        );
+    }
+
+    #[test]
+    fn cleanup_with_non_positive_target_deletes_nothing() {
+        let vfs = load(EXAMPLE).unwrap();
+        for target in [0, -5] {
+            let plan = vfs.plan_cleanup(target).unwrap();
+            assert!(plan.entries.is_empty());
+            assert_eq!(plan.total, 0);
+        }
+    }
 }
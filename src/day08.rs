@@ -1,192 +1,253 @@
 use crate::utils::read_to_vec;
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
 }
 
-fn parse_trees() -> Vec<Vec<i32>> {
-    let lines: Vec<String> = read_to_vec("data/day08.txt", |a| a.to_string());
-    lines
-        .iter()
-        .map(|line| {
-            line.trim()
-                .chars()
-                .map(|c| c.to_digit(10).unwrap() as i32)
-                .collect()
-        })
-        .collect()
+const CARDINAL: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
+#[allow(dead_code)]
+const ALL: [Direction; 8] = [
+    Direction::North,
+    Direction::NorthEast,
+    Direction::East,
+    Direction::SouthEast,
+    Direction::South,
+    Direction::SouthWest,
+    Direction::West,
+    Direction::NorthWest,
+];
+
+struct Forest {
+    rows: usize,
+    cols: usize,
+    heights: Vec<i32>,
 }
 
-fn visibility_pass(visibility: &mut Vec<Vec<bool>>, trees: &Vec<Vec<i32>>, direction: Direction) {
-    let (rows, cols) = (trees.len(), trees[0].len());
-
-    let mut highest = vec![-1; cols];
-    match direction {
-        Direction::Left | Direction::Right => {
-            for c in 0..cols {
-                for r in 0..rows {
-                    let col = if let Direction::Left = direction {
-                        cols - c - 1
-                    } else {
-                        c
-                    };
-                    let t = trees[r][col];
-                    let h = highest[r];
-                    visibility[r][col] = visibility[r][col] || t > h;
-                    highest[r] = if t > h { t } else { h }
-                }
-            }
-        }
-        Direction::Up | Direction::Down => {
-            for r in 0..rows {
-                for c in 0..cols {
-                    let row = if let Direction::Up = direction {
-                        rows - r - 1
-                    } else {
-                        r
-                    };
-                    let t = trees[row][c];
-                    let h = highest[c];
-                    visibility[row][c] = visibility[row][c] || t > h;
-                    highest[c] = if t > h { t } else { h }
-                }
-            }
-        }
-    }
+struct View {
+    visible: Vec<bool>,
+    distance: Vec<usize>,
 }
 
-fn part1(trees: &Vec<Vec<i32>>) -> usize {
-    let (rows, cols) = (trees.len(), trees[0].len());
-    let mut visibility = vec![vec![false; cols]; rows];
-
-    for dir in [
-        Direction::Up,
-        Direction::Down,
-        Direction::Left,
-        Direction::Right,
-    ] {
-        visibility_pass(&mut visibility, trees, dir)
+impl Direction {
+    fn delta(&self) -> (i64, i64) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::NorthEast => (-1, 1),
+            Direction::East => (0, 1),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (1, 0),
+            Direction::SouthWest => (1, -1),
+            Direction::West => (0, -1),
+            Direction::NorthWest => (-1, -1),
+        }
     }
-    return visibility
-        .iter()
-        .map(|row| row.iter().filter(|v| **v).count())
-        .sum();
 }
 
-#[derive(Clone)]
-struct Scenery {
-    up: i32,
-    down: i32,
-    left: i32,
-    right: i32,
-}
+impl Forest {
+    fn parse(lines: &[String]) -> Forest {
+        let rows: Vec<Vec<i32>> = lines
+            .iter()
+            .map(|line| {
+                line.trim()
+                    .chars()
+                    .map(|c| c.to_digit(10).unwrap() as i32)
+                    .collect()
+            })
+            .filter(|row: &Vec<i32>| !row.is_empty())
+            .collect();
+        Forest {
+            rows: rows.len(),
+            cols: rows.first().map_or(0, |row| row.len()),
+            heights: rows.concat(),
+        }
+    }
 
-impl Scenery {
-    fn score(&self) -> i32 {
-        self.up * self.down * self.left * self.right
+    fn cell(&self, r: i64, c: i64) -> Option<usize> {
+        match (0..self.rows as i64).contains(&r) && (0..self.cols as i64).contains(&c) {
+            true => Some(r as usize * self.cols + c as usize),
+            false => None,
+        }
     }
-}
 
-fn scenery_pass(scenery: &mut Vec<Vec<Scenery>>, trees: &Vec<Vec<i32>>, direction: Direction) {
-    let (rows, cols) = (trees.len(), trees[0].len());
-
-    match direction {
-        Direction::Left => {
-            let mut index: Vec<Vec<Option<usize>>> = vec![vec![None; rows]; 10];
-            for c in 0..cols {
-                for r in 0..rows {
-                    let t = trees[r][c] as usize;
-                    let last = index[t..].iter().filter_map(|i| i[r].as_ref()).max();
-                    scenery[r][c].left = match last {
-                        None => c as i32,
-                        Some(last) => (c - last).try_into().unwrap(),
-                    };
-                    index[t][r] = Some(c)
+    fn look(&self, direction: Direction) -> View {
+        let (dr, dc) = direction.delta();
+        let mut view = View {
+            visible: vec![false; self.heights.len()],
+            distance: vec![0; self.heights.len()],
+        };
+        let mut stack: Vec<(i32, usize)> = vec![];
+        for r in 0..self.rows as i64 {
+            for c in 0..self.cols as i64 {
+                if self.cell(r + dr, c + dc).is_some() {
+                    continue;
                 }
-            }
-        }
-        Direction::Right => {
-            let mut index: Vec<Vec<Option<usize>>> = vec![vec![None; rows]; 10];
-            for c in (0..cols).rev() {
-                for r in 0..rows {
-                    let t = trees[r][c] as usize;
-                    let last = index[t..].iter().filter_map(|i| i[r].as_ref()).min();
-                    scenery[r][c].right = match last {
-                        None => (cols - c - 1).try_into().unwrap(),
-                        Some(last) => (last - c).try_into().unwrap(),
-                    };
-                    index[t][r] = Some(c)
+                stack.clear();
+                let (mut row, mut col, mut steps) = (r, c, 0);
+                while let Some(index) = self.cell(row, col) {
+                    let height = self.heights[index];
+                    while stack.last().is_some_and(|(h, _)| *h < height) {
+                        stack.pop();
+                    }
+                    match stack.last() {
+                        Some((_, blocker)) => view.distance[index] = steps - blocker,
+                        None => {
+                            view.visible[index] = true;
+                            view.distance[index] = steps;
+                        }
+                    }
+                    stack.push((height, steps));
+                    (row, col, steps) = (row - dr, col - dc, steps + 1);
                 }
             }
         }
-        Direction::Down => {
-            let mut index: Vec<Vec<Option<usize>>> = vec![vec![None; cols]; 10];
-            for r in 0..rows {
-                for c in 0..cols {
-                    let t = trees[r][c] as usize;
-                    let last = index[t..].iter().filter_map(|i| i[c].as_ref()).max();
-                    scenery[r][c].down = match last {
-                        None => r as i32,
-                        Some(last) => (r - last).try_into().unwrap(),
-                    };
-                    index[t][c] = Some(r)
-                }
+        view
+    }
+
+    fn visible(&self, directions: &[Direction]) -> Vec<bool> {
+        let mut visible = vec![false; self.heights.len()];
+        for direction in directions {
+            let view = self.look(*direction);
+            for (v, seen) in visible.iter_mut().zip(view.visible) {
+                *v |= seen;
             }
         }
-        Direction::Up => {
-            let mut index: Vec<Vec<Option<usize>>> = vec![vec![None; cols]; 10];
-            for r in (0..rows).rev() {
-                for c in 0..cols {
-                    let t = trees[r][c] as usize;
-                    let last = index[t..].iter().filter_map(|i| i[c].as_ref()).min();
-                    scenery[r][c].up = match last {
-                        None => (rows - r - 1).try_into().unwrap(),
-                        Some(last) => (last - r).try_into().unwrap(),
-                    };
-                    index[t][c] = Some(r)
-                }
+        visible
+    }
+
+    fn scenic_scores(&self, directions: &[Direction]) -> Vec<u64> {
+        let mut scores = vec![1u64; self.heights.len()];
+        for direction in directions {
+            let view = self.look(*direction);
+            for (score, distance) in scores.iter_mut().zip(view.distance) {
+                *score *= distance as u64;
             }
         }
+        scores
     }
-}
 
-fn part2(trees: &Vec<Vec<i32>>) -> i32 {
-    let (rows, cols) = (trees.len(), trees[0].len());
-    let mut scenery = vec![
-        vec![
-            Scenery {
-                up: 0,
-                down: 0,
-                left: 0,
-                right: 0
-            };
-            cols
-        ];
-        rows
-    ];
-
-    for dir in [
-        Direction::Up,
-        Direction::Down,
-        Direction::Left,
-        Direction::Right,
-    ] {
-        scenery_pass(&mut scenery, trees, dir)
+    #[allow(dead_code)]
+    fn to_pgm(&self, values: &[u64]) -> String {
+        let max = values.iter().copied().max().unwrap_or(0).max(1);
+        let maxval = max.min(65535);
+        let mut pgm = format!("P2\n{} {}\n{}\n", self.cols, self.rows, maxval);
+        for row in values.chunks(self.cols.max(1)) {
+            let pixels: Vec<String> = row
+                .iter()
+                .map(|v| (*v as u128 * maxval as u128 / max as u128).to_string())
+                .collect();
+            pgm.push_str(&pixels.join(" "));
+            pgm.push('\n');
+        }
+        pgm
+    }
+
+    #[allow(dead_code)]
+    fn to_csv(&self, values: &[u64]) -> String {
+        values
+            .chunks(self.cols.max(1))
+            .map(|row| {
+                let cells: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+                cells.join(",") + "\n"
+            })
+            .collect()
     }
+}
+
+fn count_visible(forest: &Forest, directions: &[Direction]) -> usize {
+    forest.visible(directions).iter().filter(|v| **v).count()
+}
 
-    scenery
-        .iter()
-        .map(|r| r.iter().map(|i| i.score()).max().unwrap())
-        .max()
-        .unwrap()
+fn best_score(forest: &Forest, directions: &[Direction]) -> Option<u64> {
+    forest.scenic_scores(directions).into_iter().max()
+}
+
+fn part1(forest: &Forest) -> usize {
+    count_visible(forest, &CARDINAL)
+}
+
+fn part2(forest: &Forest) -> Option<u64> {
+    best_score(forest, &CARDINAL)
 }
 
 pub fn run() {
-    let trees = parse_trees();
+    let forest = Forest::parse(&read_to_vec("data/day08.txt", |a| a.to_string()));
     println!("== Day 08 ==");
-    println!("Part 1: {}", part1(&trees));
-    println!("Part 2: {}", part2(&trees))
+    println!("Part 1: {}", part1(&forest));
+    match part2(&forest) {
+        Some(score) => println!("Part 2: {}", score),
+        None => println!("Part 2 error: no trees"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forest(lines: &[&str]) -> Forest {
+        let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        Forest::parse(&lines)
+    }
+
+    #[test]
+    fn example() {
+        let forest = forest(&["30373", "25512", "65332", "33549", "35390"]);
+        assert_eq!(part1(&forest), 21);
+        assert_eq!(part2(&forest), Some(8));
+        let view = forest.look(Direction::West);
+        assert_eq!(&view.distance[15..20], [0, 1, 2, 1, 4]);
+        assert_eq!(&view.visible[15..20], [true, false, true, false, true]);
+    }
+
+    #[test]
+    fn scenic_score_heatmaps() {
+        let forest = forest(&["30373", "25512", "65332", "33549", "35390"]);
+        let scores = forest.scenic_scores(&CARDINAL);
+        assert_eq!(
+            forest.to_csv(&scores),
+            "0,0,0,0,0\n0,1,4,1,0\n0,6,1,2,0\n0,1,8,3,0\n0,0,0,0,0\n"
+        );
+        assert_eq!(
+            forest.to_pgm(&scores),
+            "P2\n5 5\n8\n0 0 0 0 0\n0 1 4 1 0\n0 6 1 2 0\n0 1 8 3 0\n0 0 0 0 0\n"
+        );
+        let wide = self::forest(&["123"]);
+        assert_eq!(
+            wide.to_pgm(&[0, 70000, 140000]),
+            "P2\n3 1\n65535\n0 32767 65535\n"
+        );
+        assert_eq!(wide.to_pgm(&[0, 0, 0]), "P2\n3 1\n1\n0 0 0\n");
+    }
+
+    #[test]
+    fn diagonals_see_past_cardinal_blockers() {
+        let forest = forest(&["090", "919", "090"]);
+        assert_eq!(count_visible(&forest, &CARDINAL), 8);
+        assert_eq!(count_visible(&forest, &ALL), 9);
+        assert_eq!(best_score(&forest, &CARDINAL), Some(1));
+        assert_eq!(best_score(&forest, &ALL), Some(1));
+        let view = forest.look(Direction::SouthEast);
+        assert_eq!(view.distance[4], 1);
+        assert!(view.visible[4]);
+    }
+
+    #[test]
+    fn empty_forest() {
+        let forest = forest(&[]);
+        assert_eq!(part1(&forest), 0);
+        assert_eq!(part2(&forest), None);
+    }
 }
@@ -1,69 +1,166 @@
 use crate::utils::read_some_to_vec;
 use std::collections::HashSet;
 
-enum Move {
-    Up(usize),
-    Down(usize),
-    Left(usize),
-    Right(usize),
+type Knot = (i32, i32);
+
+struct Move {
+    delta: Knot,
+    units: i32,
 }
 
-type Knot = (i32, i32);
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Mode {
+    Step,
+    #[allow(dead_code)]
+    Jump,
+}
+
+struct Rope {
+    knots: Vec<Knot>,
+    visited: Vec<HashSet<Knot>>,
+}
 
 fn to_move(line: &str) -> Option<Move> {
-    let parts: Vec<&str> = line.trim().split(" ").collect();
-    let count = parts[1].parse::<usize>();
-    match (parts[0], count) {
-        ("U", Ok(units)) => Some(Move::Up(units)),
-        ("D", Ok(units)) => Some(Move::Down(units)),
-        ("L", Ok(units)) => Some(Move::Left(units)),
-        ("R", Ok(units)) => Some(Move::Right(units)),
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let delta = match *parts.first()? {
+        "U" => (-1, 0),
+        "D" => (1, 0),
+        "L" => (0, -1),
+        "R" => (0, 1),
+        "UL" => (-1, -1),
+        "UR" => (-1, 1),
+        "DL" => (1, -1),
+        "DR" => (1, 1),
+        _ => return None,
+    };
+    match parts.get(1)?.parse() {
+        Ok(units) if units >= 0 => Some(Move { delta, units }),
         _ => None,
     }
 }
 
-fn follow(head: Knot, tail: Knot) -> Knot {
-    match (head.0 - tail.0, head.1 - tail.1) {
-        (dx, dy) if dx.abs() <= 1 && dy.abs() <= 1 => tail,
-        (-2, 0) => (tail.0 - 1, tail.1),
-        (0, -2) => (tail.0, tail.1 - 1),
-        (2, 0) => (tail.0 + 1, tail.1),
-        (0, 2) => (tail.0, tail.1 + 1),
-        (-2, -1) | (-1, -2) | (-2, -2) => (tail.0 - 1, tail.1 - 1),
-        (-2, 1) | (-1, 2) | (-2, 2) => (tail.0 - 1, tail.1 + 1),
-        (2, -1) | (1, -2) | (2, -2) => (tail.0 + 1, tail.1 - 1),
-        (2, 1) | (1, 2) | (2, 2) => (tail.0 + 1, tail.1 + 1),
-        (_, _) => panic!("invalid head/tail position"),
+fn label(index: usize) -> char {
+    match index {
+        0 => 'H',
+        1..=9 => (b'0' + index as u8) as char,
+        10..=35 => (b'a' + index as u8 - 10) as char,
+        _ => '*',
     }
 }
 
-fn do_moves(
-    visited: &mut HashSet<Knot>,
-    rope: &mut Vec<Knot>,
-    units: usize,
-    update: fn(Knot) -> Knot,
-) {
-    for _ in 0..units {
-        rope[0] = update(rope[0]);
-        for i in 1..rope.len() {
-            rope[i] = follow(rope[i - 1], rope[i])
+impl Rope {
+    fn new(length: usize) -> Rope {
+        Rope {
+            knots: vec![(0, 0); length],
+            visited: vec![HashSet::from([(0, 0)]); length],
+        }
+    }
+
+    fn settle(&mut self) {
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for i in 1..self.knots.len() {
+                let (head, tail) = (self.knots[i - 1], self.knots[i]);
+                let (dr, dc) = (head.0 - tail.0, head.1 - tail.1);
+                if dr.abs() <= 1 && dc.abs() <= 1 {
+                    continue;
+                }
+                self.knots[i] = (tail.0 + dr.signum(), tail.1 + dc.signum());
+                self.visited[i].insert(self.knots[i]);
+                moved = true;
+            }
+        }
+    }
+
+    fn shift_head(&mut self, delta: Knot) {
+        let head = &mut self.knots[0];
+        *head = (head.0 + delta.0, head.1 + delta.1);
+        self.visited[0].insert(*head);
+        self.settle();
+    }
+
+    fn apply(&mut self, m: &Move, mode: Mode) {
+        match mode {
+            Mode::Step => {
+                for _ in 0..m.units {
+                    self.shift_head(m.delta)
+                }
+            }
+            Mode::Jump => self.shift_head((m.delta.0 * m.units, m.delta.1 * m.units)),
         }
-        visited.insert(*rope.last().unwrap());
+    }
+
+    fn visited(&self, knot: usize) -> usize {
+        self.visited[knot].len()
+    }
+
+    fn tail_visited(&self) -> usize {
+        self.visited(self.knots.len() - 1)
+    }
+
+    fn bounds(&self) -> (Knot, Knot) {
+        let all = || self.visited.iter().flatten().chain(self.knots.iter());
+        let min = (
+            all().map(|k| k.0).min().unwrap_or(0),
+            all().map(|k| k.1).min().unwrap_or(0),
+        );
+        let max = (
+            all().map(|k| k.0).max().unwrap_or(0),
+            all().map(|k| k.1).max().unwrap_or(0),
+        );
+        (min, max)
+    }
+
+    fn render(&self, (min, max): (Knot, Knot)) -> String {
+        let tail = self.visited.last().unwrap();
+        (min.0..=max.0)
+            .map(|r| {
+                (min.1..=max.1)
+                    .map(|c| match self.knots.iter().position(|k| *k == (r, c)) {
+                        Some(index) => label(index),
+                        None if (r, c) == (0, 0) => 's',
+                        None if tail.contains(&(r, c)) => '#',
+                        None => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
     }
 }
 
-fn move_rope(moves: &Vec<Move>, length: usize) -> usize {
-    let mut rope = vec![(0, 0); length];
-    let mut visited = HashSet::new();
+#[allow(dead_code)]
+fn animate(moves: &[Move], length: usize, mode: Mode) -> Vec<String> {
+    let mut rope = Rope::new(length);
+    moves.iter().for_each(|m| rope.apply(m, mode));
+    let bounds = rope.bounds();
+
+    let mut rope = Rope::new(length);
+    let mut frames = vec![rope.render(bounds)];
     for m in moves {
-        match *m {
-            Move::Up(units) => do_moves(&mut visited, &mut rope, units, |(r, c)| (r - 1, c)),
-            Move::Down(units) => do_moves(&mut visited, &mut rope, units, |(r, c)| (r + 1, c)),
-            Move::Left(units) => do_moves(&mut visited, &mut rope, units, |(r, c)| (r, c - 1)),
-            Move::Right(units) => do_moves(&mut visited, &mut rope, units, |(r, c)| (r, c + 1)),
+        match mode {
+            Mode::Step => {
+                for _ in 0..m.units {
+                    rope.shift_head(m.delta);
+                    frames.push(rope.render(bounds));
+                }
+            }
+            Mode::Jump => {
+                rope.apply(m, mode);
+                frames.push(rope.render(bounds));
+            }
         }
     }
-    visited.len()
+    frames
+}
+
+fn move_rope(moves: &[Move], length: usize) -> usize {
+    let mut rope = Rope::new(length);
+    for m in moves {
+        rope.apply(m, Mode::Step);
+    }
+    rope.tail_visited()
 }
 
 pub fn run() {
@@ -72,3 +169,63 @@ pub fn run() {
     println!("Part 1: {}", move_rope(&moves, 2));
     println!("Part 2: {}", move_rope(&moves, 10))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moves(lines: &[&str]) -> Vec<Move> {
+        lines.iter().filter_map(|line| to_move(line)).collect()
+    }
+
+    #[test]
+    fn examples() {
+        let small = moves(&["R 4", "U 4", "L 3", "D 1", "R 4", "D 1", "L 5", "R 2"]);
+        assert_eq!(move_rope(&small, 2), 13);
+        assert_eq!(move_rope(&small, 10), 1);
+        let large = moves(&["R 5", "U 8", "L 8", "D 3", "R 17", "D 10", "L 25", "U 20"]);
+        assert_eq!(move_rope(&large, 10), 36);
+    }
+
+    #[test]
+    fn parses_diagonal_moves() {
+        assert!(to_move("X 1").is_none());
+        assert!(to_move("U -1").is_none());
+        assert!(to_move("U").is_none());
+        let diagonal = moves(&["DR 3"]);
+        assert_eq!(diagonal[0].delta, (1, 1));
+        assert_eq!(move_rope(&diagonal, 2), 3);
+        assert_eq!(move_rope(&diagonal, 3), 2);
+        let mut rope = Rope::new(3);
+        rope.apply(&moves(&["R 4"])[0], Mode::Step);
+        assert_eq!(rope.knots, [(0, 4), (0, 3), (0, 2)]);
+        assert_eq!(rope.visited(0), 5);
+        assert_eq!(rope.tail_visited(), 3);
+    }
+
+    #[test]
+    fn jumps_move_the_head_in_one_go() {
+        let mut rope = Rope::new(3);
+        rope.apply(&moves(&["R 4"])[0], Mode::Jump);
+        assert_eq!(rope.knots, [(0, 4), (0, 3), (0, 2)]);
+        assert_eq!(rope.visited(0), 2);
+        assert_eq!(rope.tail_visited(), 3);
+        rope.apply(&moves(&["UL 2"])[0], Mode::Jump);
+        assert_eq!(rope.knots, [(-2, 2), (-1, 2), (0, 2)]);
+        assert_eq!(rope.visited(1), 5);
+    }
+
+    #[test]
+    fn animation_frames() {
+        let moves = moves(&["R 2", "U 1"]);
+        let frames = animate(&moves, 2, Mode::Step);
+        assert_eq!(frames, ["...\nH..", "...\n1H.", "...\ns1H", "..H\ns1."]);
+        let frames = animate(&moves, 2, Mode::Jump);
+        assert_eq!(frames, ["...\nH..", "...\ns1H", "..H\ns1."]);
+        let frames = animate(&self::moves(&["L 3"]), 3, Mode::Step);
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[3], "H12s");
+        let frames = animate(&self::moves(&["R 3"]), 2, Mode::Step);
+        assert_eq!(frames.last().unwrap(), "s#1H");
+    }
+}
@@ -1,135 +1,783 @@
+use crate::ocr::recognize;
 use clock_circuit::Program;
+use debugger::{Breakpoint, Debugger};
 
 mod clock_circuit {
-    use crate::utils::read_some_to_vec;
+    use std::fmt::{self, Display, Formatter};
+    use std::fs::read_to_string;
+
+    pub const REGISTER_NAMES: [&str; 4] = ["x", "y", "z", "w"];
+
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum Operand {
+        Register(usize),
+        Immediate(i32),
+    }
+
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum ArgKind {
+        Register,
+        Value,
+    }
+
+    pub enum Flow {
+        Next,
+        Jump(i32),
+        Halt,
+    }
+
+    #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+    pub struct Registers([i32; 4]);
+
+    pub struct OpSpec {
+        pub mnemonic: &'static str,
+        pub cycles: usize,
+        pub args: &'static [ArgKind],
+        pub execute: fn(&mut Registers, &[Operand]) -> Flow,
+    }
 
     #[derive(Copy, Clone)]
-    enum Instruction {
-        AddX(usize, i32),
-        Noop(usize),
-        Start,
-        End,
+    pub struct Instruction {
+        spec: &'static OpSpec,
+        args: [Operand; 2],
+    }
+
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    pub enum AsmErrorKind {
+        UnknownMnemonic(String),
+        WrongArgCount(usize, usize),
+        BadOperand(String),
+    }
+
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    pub struct AsmError {
+        pub line: usize,
+        pub kind: AsmErrorKind,
+    }
+
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub struct Snapshot {
+        pc: usize,
+        cycle: usize,
+        remaining: usize,
+        halted: bool,
+        registers: Registers,
     }
 
     pub struct Program {
-        counter: usize,
+        pc: usize,
         cycle: usize,
-        register: i32,
-        current: Instruction,
+        remaining: usize,
+        halted: bool,
+        registers: Registers,
         instructions: Vec<Instruction>,
     }
 
-    fn to_instruction(line: &str) -> Option<Instruction> {
-        let parts: Vec<&str> = line.trim().split(" ").collect();
-        match parts[..] {
-            ["noop"] => Some(Instruction::Noop(1)),
-            ["addx", value] => Some(Instruction::AddX(2, value.parse().unwrap())),
-            _ => None,
+    fn register(args: &[Operand]) -> usize {
+        match args[0] {
+            Operand::Register(index) => index,
+            Operand::Immediate(_) => panic!("operand is not a register"),
+        }
+    }
+
+    fn noop(_: &mut Registers, _: &[Operand]) -> Flow {
+        Flow::Next
+    }
+
+    fn addx(registers: &mut Registers, args: &[Operand]) -> Flow {
+        registers.0[0] += registers.get(args[0]);
+        Flow::Next
+    }
+
+    fn set(registers: &mut Registers, args: &[Operand]) -> Flow {
+        registers.0[register(args)] = registers.get(args[1]);
+        Flow::Next
+    }
+
+    fn add(registers: &mut Registers, args: &[Operand]) -> Flow {
+        registers.0[register(args)] += registers.get(args[1]);
+        Flow::Next
+    }
+
+    fn sub(registers: &mut Registers, args: &[Operand]) -> Flow {
+        registers.0[register(args)] -= registers.get(args[1]);
+        Flow::Next
+    }
+
+    fn mul(registers: &mut Registers, args: &[Operand]) -> Flow {
+        registers.0[register(args)] *= registers.get(args[1]);
+        Flow::Next
+    }
+
+    fn jmp(registers: &mut Registers, args: &[Operand]) -> Flow {
+        Flow::Jump(registers.get(args[0]))
+    }
+
+    fn jnz(registers: &mut Registers, args: &[Operand]) -> Flow {
+        match registers.get(args[0]) {
+            0 => Flow::Next,
+            _ => Flow::Jump(registers.get(args[1])),
+        }
+    }
+
+    fn halt(_: &mut Registers, _: &[Operand]) -> Flow {
+        Flow::Halt
+    }
+
+    pub static ISA: [OpSpec; 9] = [
+        OpSpec {
+            mnemonic: "noop",
+            cycles: 1,
+            args: &[],
+            execute: noop,
+        },
+        OpSpec {
+            mnemonic: "addx",
+            cycles: 2,
+            args: &[ArgKind::Value],
+            execute: addx,
+        },
+        OpSpec {
+            mnemonic: "set",
+            cycles: 1,
+            args: &[ArgKind::Register, ArgKind::Value],
+            execute: set,
+        },
+        OpSpec {
+            mnemonic: "add",
+            cycles: 2,
+            args: &[ArgKind::Register, ArgKind::Value],
+            execute: add,
+        },
+        OpSpec {
+            mnemonic: "sub",
+            cycles: 2,
+            args: &[ArgKind::Register, ArgKind::Value],
+            execute: sub,
+        },
+        OpSpec {
+            mnemonic: "mul",
+            cycles: 3,
+            args: &[ArgKind::Register, ArgKind::Value],
+            execute: mul,
+        },
+        OpSpec {
+            mnemonic: "jmp",
+            cycles: 2,
+            args: &[ArgKind::Value],
+            execute: jmp,
+        },
+        OpSpec {
+            mnemonic: "jnz",
+            cycles: 2,
+            args: &[ArgKind::Value, ArgKind::Value],
+            execute: jnz,
+        },
+        OpSpec {
+            mnemonic: "halt",
+            cycles: 1,
+            args: &[],
+            execute: halt,
+        },
+    ];
+
+    impl Registers {
+        pub fn values(&self) -> &[i32; 4] {
+            &self.0
+        }
+
+        pub fn get(&self, operand: Operand) -> i32 {
+            match operand {
+                Operand::Register(index) => self.0[index],
+                Operand::Immediate(value) => value,
+            }
         }
     }
 
+    impl Instruction {
+        pub fn mnemonic(&self) -> &'static str {
+            self.spec.mnemonic
+        }
+
+        pub fn cycles(&self) -> usize {
+            self.spec.cycles
+        }
+
+        pub fn args(&self) -> &[Operand] {
+            &self.args[..self.spec.args.len()]
+        }
+    }
+
+    impl Display for Operand {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match self {
+                Operand::Register(index) => write!(f, "{}", REGISTER_NAMES[*index]),
+                Operand::Immediate(value) => write!(f, "{}", value),
+            }
+        }
+    }
+
+    impl Display for Instruction {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            write!(f, "{}", self.mnemonic())?;
+            for arg in self.args() {
+                write!(f, " {}", arg)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl Display for AsmError {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match &self.kind {
+                AsmErrorKind::UnknownMnemonic(mnemonic) => {
+                    write!(f, "line {}: unknown mnemonic '{}'", self.line, mnemonic)
+                }
+                AsmErrorKind::WrongArgCount(expected, found) => write!(
+                    f,
+                    "line {}: expected {} operands, found {}",
+                    self.line, expected, found
+                ),
+                AsmErrorKind::BadOperand(operand) => {
+                    write!(f, "line {}: invalid operand '{}'", self.line, operand)
+                }
+            }
+        }
+    }
+
+    fn to_operand(text: &str, kind: ArgKind) -> Option<Operand> {
+        match REGISTER_NAMES.iter().position(|r| *r == text) {
+            Some(index) => Some(Operand::Register(index)),
+            None if kind == ArgKind::Value => text.parse().ok().map(Operand::Immediate),
+            None => None,
+        }
+    }
+
+    fn to_instruction(
+        line: &str,
+        isa: &'static [OpSpec],
+    ) -> Result<Option<Instruction>, AsmErrorKind> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let (mnemonic, operands) = match parts.split_first() {
+            Some((mnemonic, operands)) => (*mnemonic, operands),
+            None => return Ok(None),
+        };
+        let spec = isa
+            .iter()
+            .find(|op| op.mnemonic == mnemonic)
+            .ok_or_else(|| AsmErrorKind::UnknownMnemonic(mnemonic.to_string()))?;
+        if operands.len() != spec.args.len() {
+            return Err(AsmErrorKind::WrongArgCount(spec.args.len(), operands.len()));
+        }
+        let mut args = [Operand::Immediate(0); 2];
+        for (i, (text, kind)) in operands.iter().zip(spec.args).enumerate() {
+            args[i] = to_operand(text, *kind)
+                .ok_or_else(|| AsmErrorKind::BadOperand(text.to_string()))?;
+        }
+        Ok(Some(Instruction { spec, args }))
+    }
+
+    pub fn assemble(source: &str, isa: &'static [OpSpec]) -> Result<Vec<Instruction>, AsmError> {
+        source
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| match to_instruction(line, isa) {
+                Ok(instruction) => instruction.map(Ok),
+                Err(kind) => Some(Err(AsmError { line: i + 1, kind })),
+            })
+            .collect()
+    }
+
     impl Program {
-        pub fn new(filename: &str) -> Program {
-            Program {
-                counter: 0,
+        pub fn new(instructions: Vec<Instruction>) -> Program {
+            let mut program = Program {
+                pc: 0,
                 cycle: 1,
-                register: 1,
-                current: Instruction::Start,
-                instructions: read_some_to_vec(filename, to_instruction),
-            }
+                remaining: 0,
+                halted: false,
+                registers: Registers::default(),
+                instructions,
+            };
+            program.reset();
+            program
+        }
+
+        pub fn load(filename: &str) -> Result<Program, AsmError> {
+            let source = read_to_string(filename).unwrap_or_default();
+            Ok(Program::new(assemble(&source, &ISA)?))
         }
 
         pub fn len(&self) -> usize {
             self.instructions.len()
         }
 
-        fn inc(&mut self) -> Instruction {
-            self.counter += 1;
-            if self.counter < self.len() {
-                self.instructions[self.counter]
-            } else {
-                Instruction::End
+        fn fetch(&mut self, pc: i64) {
+            match pc {
+                pc if pc < 0 || pc >= self.len() as i64 => self.halted = true,
+                pc => {
+                    self.pc = pc as usize;
+                    self.remaining = self.instructions[self.pc].cycles();
+                }
             }
         }
 
         pub fn next(&mut self) {
-            (self.current, self.cycle) = match (self.current, self.cycle) {
-                (Instruction::Start, 1) => (self.instructions[0], 1),
-                (Instruction::End, cycle) => (Instruction::End, cycle),
-                (Instruction::AddX(2, val), cycle) => (Instruction::AddX(1, val), cycle + 1),
-                (Instruction::AddX(1, val), cycle) => {
-                    self.register += val;
-                    (self.inc(), cycle + 1)
-                }
-                (Instruction::Noop(1), cycle) => (self.inc(), cycle + 1),
-                _ => panic!("Invalid program state"),
+            if self.halted {
+                return;
+            }
+            self.cycle += 1;
+            self.remaining -= 1;
+            if self.remaining > 0 {
+                return;
+            }
+            let instruction = self.instructions[self.pc];
+            match (instruction.spec.execute)(&mut self.registers, instruction.args()) {
+                Flow::Next => self.fetch(self.pc as i64 + 1),
+                Flow::Jump(offset) => self.fetch(self.pc as i64 + offset as i64),
+                Flow::Halt => self.halted = true,
             }
         }
 
         pub fn reset(&mut self) {
-            self.current = Instruction::Start;
             self.cycle = 1;
-            self.register = 1;
-            self.counter = 0;
+            self.halted = false;
+            self.registers = Registers::default();
+            self.registers.0[0] = 1;
+            self.fetch(0);
         }
 
         pub fn signal_strength(&self) -> i32 {
-            (self.cycle as i32) * self.register
+            (self.cycle as i32) * self.value()
         }
 
         pub fn is_running(&self) -> bool {
-            match self.current {
-                Instruction::End => false,
-                _ => true,
-            }
+            !self.halted
+        }
+
+        pub fn is_fetched(&self) -> bool {
+            self.current()
+                .is_some_and(|instruction| self.remaining == instruction.cycles())
         }
 
         pub fn cycle(&self) -> usize {
             self.cycle
         }
 
+        pub fn pc(&self) -> usize {
+            self.pc
+        }
+
+        pub fn current(&self) -> Option<&Instruction> {
+            match self.halted {
+                true => None,
+                false => self.instructions.get(self.pc),
+            }
+        }
+
+        pub fn registers(&self) -> &Registers {
+            &self.registers
+        }
+
         pub fn value(&self) -> i32 {
-            self.register
+            self.registers.0[0]
+        }
+
+        pub fn snapshot(&self) -> Snapshot {
+            Snapshot {
+                pc: self.pc,
+                cycle: self.cycle,
+                remaining: self.remaining,
+                halted: self.halted,
+                registers: self.registers,
+            }
+        }
+
+        pub fn restore(&mut self, snapshot: &Snapshot) {
+            self.pc = snapshot.pc;
+            self.cycle = snapshot.cycle;
+            self.remaining = snapshot.remaining;
+            self.halted = snapshot.halted;
+            self.registers = snapshot.registers;
         }
     }
 }
 
-fn part1(program: &mut Program) -> i32 {
-    let mut total = 0;
-    while program.cycle() < 221 {
-        if program.cycle() >= 20 && (program.cycle() - 20) % 40 == 0 {
-            total += program.signal_strength()
+mod debugger {
+    use super::clock_circuit::{Instruction, Program, Registers, Snapshot, REGISTER_NAMES};
+
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum Breakpoint {
+        Cycle(usize),
+        #[allow(dead_code)]
+        Pc(usize),
+    }
+
+    #[derive(Copy, Clone)]
+    pub struct TraceRow {
+        pub cycle: usize,
+        pub pc: usize,
+        pub instruction: Option<Instruction>,
+        pub registers: Registers,
+    }
+
+    pub struct Debugger {
+        program: Program,
+        breakpoints: Vec<Breakpoint>,
+        history: Vec<Snapshot>,
+        trace: Vec<TraceRow>,
+        paused: bool,
+    }
+
+    impl TraceRow {
+        pub fn beam(&self) -> usize {
+            (self.cycle - 1) % 40
+        }
+
+        pub fn is_lit(&self) -> bool {
+            (self.registers.values()[0] - self.beam() as i32).abs() < 2
         }
-        program.next()
     }
 
-    total
+    impl Breakpoint {
+        fn hit(&self, program: &Program) -> bool {
+            match self {
+                Breakpoint::Cycle(cycle) => program.cycle() == *cycle,
+                Breakpoint::Pc(pc) => program.is_fetched() && program.pc() == *pc,
+            }
+        }
+    }
+
+    impl Debugger {
+        pub fn new(program: Program) -> Debugger {
+            Debugger {
+                program,
+                breakpoints: vec![],
+                history: vec![],
+                trace: vec![],
+                paused: false,
+            }
+        }
+
+        pub fn program(&self) -> &Program {
+            &self.program
+        }
+
+        pub fn trace(&self) -> &[TraceRow] {
+            &self.trace
+        }
+
+        pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+            if !self.breakpoints.contains(&breakpoint) {
+                self.breakpoints.push(breakpoint)
+            }
+        }
+
+        pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) {
+            self.breakpoints.retain(|b| *b != breakpoint)
+        }
+
+        pub fn current(&self) -> TraceRow {
+            TraceRow {
+                cycle: self.program.cycle(),
+                pc: self.program.pc(),
+                instruction: self.program.current().copied(),
+                registers: *self.program.registers(),
+            }
+        }
+
+        pub fn step(&mut self) -> bool {
+            if !self.program.is_running() {
+                return false;
+            }
+            self.trace.push(self.current());
+            self.history.push(self.program.snapshot());
+            self.program.next();
+            self.paused = false;
+            true
+        }
+
+        fn breakpoint(&self) -> Option<Breakpoint> {
+            self.breakpoints
+                .iter()
+                .find(|b| b.hit(&self.program))
+                .copied()
+        }
+
+        pub fn run_until_break(&mut self) -> Option<Breakpoint> {
+            if !self.paused {
+                if let Some(breakpoint) = self.breakpoint() {
+                    self.paused = true;
+                    return Some(breakpoint);
+                }
+            }
+            while self.step() {
+                if let Some(breakpoint) = self.breakpoint() {
+                    self.paused = true;
+                    return Some(breakpoint);
+                }
+            }
+            None
+        }
+
+        pub fn rewind(&mut self, steps: usize) -> usize {
+            let steps = steps.min(self.history.len());
+            let keep = self.history.len() - steps;
+            if let Some(snapshot) = self.history.get(keep) {
+                self.program.restore(snapshot);
+                self.paused = false;
+            }
+            self.history.truncate(keep);
+            self.trace.truncate(keep);
+            steps
+        }
+
+        pub fn replay(&mut self, cycle: usize) {
+            let current = self.program.cycle();
+            if cycle < current {
+                self.rewind(current - cycle);
+            }
+            while self.program.cycle() < cycle && self.step() {}
+        }
+
+        #[allow(dead_code)]
+        pub fn to_csv(&self) -> String {
+            let mut csv = format!(
+                "cycle,pc,instruction,{},beam,lit\n",
+                REGISTER_NAMES.join(",")
+            );
+            for row in &self.trace {
+                let instruction = row.instruction.map(|i| i.to_string()).unwrap_or_default();
+                let registers: Vec<String> = row
+                    .registers
+                    .values()
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect();
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{}\n",
+                    row.cycle,
+                    row.pc,
+                    instruction,
+                    registers.join(","),
+                    row.beam(),
+                    row.is_lit() as u8
+                ));
+            }
+            csv
+        }
+    }
 }
 
-fn part2(program: &mut Program) -> String {
-    let mut line = [' '; 40];
-    let mut lines: Vec<String> = vec![];
-    while program.is_running() {
-        let index = (program.cycle() - 1) % 40;
-        line[index] = match (program.value() - (index as i32)).abs() {
-            diff if diff < 2 => '#',
-            _ => '.',
-        };
-        if program.cycle() % 40 == 0 {
-            lines.push(line.iter().collect());
-            line.fill(' ')
-        };
-        program.next()
+const SCREEN_CYCLES: usize = 240;
+
+fn part1(debugger: &mut Debugger) -> i32 {
+    (20..=220)
+        .step_by(40)
+        .for_each(|cycle| debugger.add_breakpoint(Breakpoint::Cycle(cycle)));
+    let mut total = 0;
+    while let Some(breakpoint) = debugger.run_until_break() {
+        total += debugger.program().signal_strength();
+        debugger.remove_breakpoint(breakpoint);
     }
 
-    lines.join("\n")
+    total
+}
+
+fn render(debugger: &mut Debugger) -> String {
+    debugger.replay(SCREEN_CYCLES + 1);
+    debugger
+        .trace()
+        .chunks_exact(40)
+        .map(|row| {
+            row.iter()
+                .map(|pixel| if pixel.is_lit() { '#' } else { '.' })
+                .collect()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
 }
 
 pub fn run() {
-    let mut program = Program::new("data/day10.txt");
     println!("== Day 10 ==");
-    println!("Part 1: {}", part1(&mut program));
-    program.reset();
-    println!("Part 2");
-    println!("{}", part2(&mut program))
+    let mut debugger = match Program::load("data/day10.txt") {
+        Ok(program) => Debugger::new(program),
+        Err(error) => {
+            println!("Error: {}", error);
+            return;
+        }
+    };
+    println!("Part 1: {}", part1(&mut debugger));
+    let screen = render(&mut debugger);
+    match recognize(&screen) {
+        Ok(letters) => println!("Part 2: {}", letters),
+        Err(error) => {
+            println!("Part 2 error: {}", error);
+            println!("{}", screen)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::clock_circuit::{assemble, AsmError, AsmErrorKind, ISA};
+    use super::*;
+
+    fn debugger(source: &str) -> Debugger {
+        Debugger::new(Program::new(assemble(source, &ISA).unwrap()))
+    }
+
+    fn error(source: &str) -> AsmError {
+        assemble(source, &ISA).err().unwrap()
+    }
+
+    fn execute(source: &str) -> Program {
+        let mut program = Program::new(assemble(source, &ISA).unwrap());
+        while program.is_running() {
+            program.next();
+        }
+        program
+    }
+
+    fn breaks(debugger: &mut Debugger) -> Vec<usize> {
+        let mut cycles = vec![];
+        while debugger.run_until_break().is_some() {
+            cycles.push(debugger.program().cycle());
+        }
+        cycles
+    }
+
+    #[test]
+    fn assembler_rejects_unknown_mnemonics_with_line_numbers() {
+        let error = error("noop\n\naddx 1\nmov x 2\nnoop");
+        assert_eq!(
+            error,
+            AsmError {
+                line: 4,
+                kind: AsmErrorKind::UnknownMnemonic("mov".to_string())
+            }
+        );
+        assert_eq!(error.to_string(), "line 4: unknown mnemonic 'mov'");
+    }
+
+    #[test]
+    fn assembler_checks_operands() {
+        assert_eq!(error("addx").kind, AsmErrorKind::WrongArgCount(1, 0));
+        assert_eq!(
+            error("noop\nset x 1 2").kind,
+            AsmErrorKind::WrongArgCount(2, 3)
+        );
+        assert_eq!(
+            error("addx three").kind,
+            AsmErrorKind::BadOperand("three".to_string())
+        );
+        assert_eq!(
+            error("noop\nset 1 2"),
+            AsmError {
+                line: 2,
+                kind: AsmErrorKind::BadOperand("1".to_string())
+            }
+        );
+        let instructions = assemble("  set y -3\n\nadd x y\n", &ISA).unwrap();
+        let listing: Vec<String> = instructions.iter().map(|i| i.to_string()).collect();
+        assert_eq!(listing, ["set y -3", "add x y"]);
+        assert_eq!(instructions[1].cycles(), 2);
+    }
+
+    #[test]
+    fn jumps_and_halt_control_execution() {
+        let program = execute("set y 3\nadd z y\nsub y 1\njnz y -2\nhalt\nset z 100");
+        assert_eq!(program.registers().values(), &[1, 0, 6, 0]);
+        assert_eq!(program.cycle(), 1 + 1 + 3 * (2 + 2 + 2) + 1);
+        let program = execute("jmp 2\naddx 10\nmul x 5");
+        assert_eq!(program.value(), 5);
+        assert_eq!(program.cycle(), 6);
+        let program = execute("addx 4\njmp -5\naddx 10");
+        assert_eq!(program.value(), 5);
+        assert!(!program.is_running());
+    }
+
+    #[test]
+    fn pc_breakpoint_fires_once_at_fetch() {
+        let mut debugger = debugger("noop\naddx 3\nmul x 2\nnoop");
+        debugger.add_breakpoint(Breakpoint::Pc(2));
+        assert_eq!(breaks(&mut debugger), [4]);
+        assert_eq!(debugger.program().value(), 8);
+    }
+
+    #[test]
+    fn breakpoints_fire_on_initial_state() {
+        let mut debugger = debugger("addx 2\nnoop");
+        debugger.add_breakpoint(Breakpoint::Pc(0));
+        debugger.add_breakpoint(Breakpoint::Cycle(1));
+        assert_eq!(debugger.run_until_break(), Some(Breakpoint::Pc(0)));
+        assert_eq!(debugger.program().cycle(), 1);
+        assert_eq!(debugger.run_until_break(), None);
+        assert_eq!(debugger.program().cycle(), 4);
+    }
+
+    #[test]
+    fn breakpoints_can_be_removed() {
+        let mut debugger = debugger("noop\nnoop\nnoop\nnoop");
+        debugger.add_breakpoint(Breakpoint::Cycle(2));
+        debugger.add_breakpoint(Breakpoint::Cycle(2));
+        debugger.add_breakpoint(Breakpoint::Cycle(4));
+        debugger.remove_breakpoint(Breakpoint::Cycle(2));
+        assert_eq!(breaks(&mut debugger), [4]);
+    }
+
+    #[test]
+    fn rewind_restores_earlier_state() {
+        let mut debugger = debugger("addx 3\naddx -5\nnoop");
+        while debugger.step() {}
+        assert_eq!(debugger.program().value(), -1);
+        assert_eq!(debugger.rewind(3), 3);
+        assert_eq!(debugger.program().cycle(), 3);
+        assert_eq!(debugger.program().value(), 4);
+        assert_eq!(debugger.trace().len(), 2);
+        assert!(debugger.program().is_running());
+        assert_eq!(debugger.rewind(10), 2);
+        assert_eq!(debugger.program().cycle(), 1);
+        assert_eq!(debugger.program().value(), 1);
+        assert_eq!(debugger.rewind(1), 0);
+    }
+
+    #[test]
+    fn replay_moves_in_both_directions() {
+        let mut debugger = debugger("addx 3\naddx -5\nnoop");
+        debugger.replay(4);
+        assert_eq!(debugger.program().value(), 4);
+        assert_eq!(debugger.trace().len(), 3);
+        debugger.replay(2);
+        assert_eq!(debugger.program().cycle(), 2);
+        assert_eq!(debugger.program().value(), 1);
+        debugger.replay(100);
+        assert!(!debugger.program().is_running());
+        assert_eq!(debugger.program().cycle(), 6);
+        let values: Vec<i32> = debugger
+            .trace()
+            .iter()
+            .map(|row| row.registers.values()[0])
+            .collect();
+        assert_eq!(values, [1, 1, 4, 4, -1]);
+    }
+
+    #[test]
+    fn trace_exports_as_csv() {
+        let mut debugger = debugger("addx 3\nset y -2\nnoop");
+        debugger.replay(5);
+        let csv = debugger.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            rows,
+            [
+                "cycle,pc,instruction,x,y,z,w,beam,lit",
+                "1,0,addx 3,1,0,0,0,0,1",
+                "2,0,addx 3,1,0,0,0,1,1",
+                "3,1,set y -2,4,0,0,0,2,0",
+                "4,2,noop,4,-2,0,0,3,1",
+            ]
+        );
+        debugger.rewind(4);
+        assert_eq!(debugger.to_csv(), "cycle,pc,instruction,x,y,z,w,beam,lit\n");
+    }
 }
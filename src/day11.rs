@@ -1,150 +1,782 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
 use std::fs::read_to_string;
+use std::hash::Hash;
+use std::mem;
+use std::str::FromStr;
 
-#[derive(Copy, Clone)]
-enum Arg {
+use crate::maths::bigint::BigInt;
+use crate::maths::checked::{with_fallback, Checked, Op, Overflow};
+use crate::maths::integer::Integer;
+use crate::maths::number_theory::checked_lcm;
+
+const MAX_NESTING: usize = 64;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Token {
+    Number(i64),
     Old,
-    Constant(usize),
+    Symbol(char),
 }
 
-#[derive(Copy, Clone)]
-enum Operation {
-    Add(Arg, Arg),
-    Multiply(Arg, Arg),
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Term {
+    Old,
+    Constant(i64),
+    Negate,
+    Binary(Op),
 }
 
-impl Operation {
-    fn get_result(&self, old: usize) -> usize {
-        match self {
-            Operation::Add(Arg::Old, Arg::Old) => old + old,
-            Operation::Add(Arg::Old, Arg::Constant(value))
-            | Operation::Add(Arg::Constant(value), Arg::Old) => old + value,
-            Operation::Multiply(Arg::Old, Arg::Old) => old * old,
-            Operation::Multiply(Arg::Old, Arg::Constant(value))
-            | Operation::Multiply(Arg::Constant(value), Arg::Old) => old * value,
-            _ => panic!("invalid operation"),
-        }
-    }
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Expression {
+    postfix: Vec<Term>,
 }
 
-type Test = (usize, usize, usize);
+struct ExpressionParser {
+    line: usize,
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+    output: Vec<Term>,
+}
 
-struct Monkey {
-    items: Vec<usize>,
-    operation: Operation,
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Test {
+    divisor: i64,
+    if_true: usize,
+    if_false: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Rule {
+    items: Vec<i64>,
+    operation: Expression,
     test: Test,
+}
+
+#[derive(Debug, Clone)]
+struct Item<T> {
+    id: usize,
+    worry: T,
+    trace: Vec<usize>,
+}
+
+struct Monkey<'a, T> {
+    rule: &'a Rule,
+    divisor: T,
+    items: Vec<Item<T>>,
     inspection_count: usize,
 }
 
-enum WorryManagement {
+enum WorryManagement<T> {
     Decrease,
-    Modulo(usize),
-}
-
-fn inspect_and_throw(monkeys: &mut Vec<Monkey>, index: usize, worry_management: &WorryManagement) {
-    monkeys.push(Monkey {
-        items: vec![],
-        operation: monkeys[index].operation,
-        test: monkeys[index].test,
-        inspection_count: monkeys[index].inspection_count + monkeys[index].items.len(),
-    });
-    let mut monkey = monkeys.swap_remove(index);
-    for item in monkey.items.into_iter() {
-        let worry = match (monkey.operation.get_result(item), worry_management) {
-            (worry, WorryManagement::Decrease) => worry / 3,
-            (worry, WorryManagement::Modulo(value)) => worry % value,
-        };
-        let index = match monkey.test {
-            (divisor, m_true, _) if worry % divisor == 0 => m_true,
-            (_, _, m_false) => m_false,
+    Modulo(T),
+    Exact,
+}
+
+struct Trajectory {
+    visits: Vec<usize>,
+    rounds: Vec<usize>,
+    cycle_start: Option<usize>,
+}
+
+struct Simulation<'a, T> {
+    monkeys: Vec<Monkey<'a, T>>,
+    worry_management: WorryManagement<T>,
+    tracing: bool,
+    ops: Checked,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct ParseError {
+    line: usize,
+    message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Old => write!(f, "old"),
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+        }
+    }
+}
+
+fn error(line: usize, message: impl Into<String>) -> ParseError {
+    ParseError {
+        line,
+        message: message.into(),
+    }
+}
+
+fn to_op(symbol: char) -> Op {
+    match symbol {
+        '+' => Op::Add,
+        '-' => Op::Subtract,
+        '*' => Op::Multiply,
+        '/' => Op::Divide,
+        _ => Op::Remainder,
+    }
+}
+
+fn tokenize(line: usize, source: &str) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            c if c.is_ascii_digit() => {
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let digits: String = chars[start..i].iter().collect();
+                match digits.parse() {
+                    Ok(value) => tokens.push(Token::Number(value)),
+                    Err(_) => return Err(error(line, format!("constant {} is too large", digits))),
+                }
+            }
+            c if c.is_alphabetic() => {
+                while i < chars.len() && chars[i].is_alphanumeric() {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                match word.as_str() {
+                    "old" => tokens.push(Token::Old),
+                    _ => return Err(error(line, format!("unknown variable '{}'", word))),
+                }
+            }
+            c @ ('+' | '-' | '*' | '/' | '%' | '(' | ')') => {
+                tokens.push(Token::Symbol(c));
+                i += 1;
+            }
+            c => return Err(error(line, format!("unexpected character '{}'", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+impl ExpressionParser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<(), ParseError>) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err(error(self.line, "expression is nested too deeply"));
+        }
+        parse(self)?;
+        self.depth -= 1;
+        Ok(())
+    }
+
+    fn binary(
+        &mut self,
+        symbols: &[char],
+        operand: fn(&mut Self) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        operand(self)?;
+        while let Some(Token::Symbol(symbol)) = self.peek() {
+            if !symbols.contains(&symbol) {
+                break;
+            }
+            self.position += 1;
+            operand(self)?;
+            self.output.push(Term::Binary(to_op(symbol)));
+        }
+        Ok(())
+    }
+
+    fn expression(&mut self) -> Result<(), ParseError> {
+        self.binary(&['+', '-'], Self::product)
+    }
+
+    fn product(&mut self) -> Result<(), ParseError> {
+        self.binary(&['*', '/', '%'], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(Token::Symbol('-')) => {
+                self.position += 1;
+                self.nested(Self::unary)?;
+                self.output.push(Term::Negate);
+                Ok(())
+            }
+            _ => self.atom(),
+        }
+    }
+
+    fn atom(&mut self) -> Result<(), ParseError> {
+        match self.next() {
+            Some(Token::Old) => self.output.push(Term::Old),
+            Some(Token::Number(value)) => self.output.push(Term::Constant(value)),
+            Some(Token::Symbol('(')) => {
+                self.nested(Self::expression)?;
+                if self.next() != Some(Token::Symbol(')')) {
+                    return Err(error(self.line, "expected ')'"));
+                }
+            }
+            Some(token) => return Err(error(self.line, format!("unexpected '{}'", token))),
+            None => return Err(error(self.line, "unexpected end of expression")),
+        }
+        Ok(())
+    }
+}
+
+impl Expression {
+    fn parse(line: usize, source: &str) -> Result<Expression, ParseError> {
+        let mut parser = ExpressionParser {
+            line,
+            tokens: tokenize(line, source)?,
+            position: 0,
+            depth: 0,
+            output: vec![],
         };
-        monkeys[index].items.push(worry);
-        monkey.inspection_count += 1
+        parser.expression()?;
+        match parser.peek() {
+            Some(token) => Err(error(line, format!("unexpected '{}'", token))),
+            None => Ok(Expression {
+                postfix: parser.output,
+            }),
+        }
+    }
+
+    fn evaluate<T: Integer>(&self, old: &T, ops: &Checked) -> Result<T, Overflow> {
+        let mut stack: Vec<T> = vec![];
+        for term in &self.postfix {
+            let value = match term {
+                Term::Old => old.clone(),
+                Term::Constant(value) => ops.convert(*value)?,
+                Term::Negate => {
+                    let value = stack.pop().unwrap();
+                    ops.sub(&T::zero(), &value)?
+                }
+                Term::Binary(op) => {
+                    let rhs = stack.pop().unwrap();
+                    let lhs = stack.pop().unwrap();
+                    match op {
+                        Op::Add => ops.add(&lhs, &rhs)?,
+                        Op::Subtract => ops.sub(&lhs, &rhs)?,
+                        Op::Multiply => ops.mul(&lhs, &rhs)?,
+                        Op::Divide => ops.div(&lhs, &rhs)?,
+                        _ => ops.rem(&lhs, &rhs)?,
+                    }
+                }
+            };
+            stack.push(value);
+        }
+        Ok(stack.pop().unwrap())
+    }
+
+    fn preserves_congruence(&self) -> bool {
+        self.postfix
+            .iter()
+            .all(|term| !matches!(term, Term::Binary(Op::Divide | Op::Remainder)))
     }
 }
 
-fn get_last(line: &str) -> usize {
-    line.trim().split(" ").last().unwrap().parse().unwrap()
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut stack: Vec<(String, bool)> = vec![];
+        let operand = |stack: &mut Vec<(String, bool)>| match stack.pop().unwrap() {
+            (text, true) => format!("({})", text),
+            (text, false) => text,
+        };
+        for term in &self.postfix {
+            let entry = match term {
+                Term::Old => ("old".to_string(), false),
+                Term::Constant(value) => (value.to_string(), false),
+                Term::Negate => (format!("-{}", operand(&mut stack)), false),
+                Term::Binary(op) => {
+                    let rhs = operand(&mut stack);
+                    let lhs = operand(&mut stack);
+                    (format!("{} {} {}", lhs, op, rhs), true)
+                }
+            };
+            stack.push(entry);
+        }
+        write!(
+            f,
+            "{}",
+            stack.pop().map(|(text, _)| text).unwrap_or_default()
+        )
+    }
 }
 
-fn parse_items(line: &str) -> Vec<usize> {
-    line[18..]
-        .trim()
-        .split(",")
-        .map(|i| i.trim().parse().unwrap())
-        .collect()
+struct Lines<'a> {
+    lines: std::iter::Peekable<std::iter::Enumerate<std::str::Lines<'a>>>,
+    last: usize,
 }
 
-fn parse_op(line: &str) -> Operation {
-    let parts: Vec<&str> = line.trim().split(" ").collect();
-    let arg0 = match parts[3] {
-        "old" => Arg::Old,
-        constant => Arg::Constant(constant.parse().unwrap()),
-    };
-    let arg1 = match parts[5] {
-        "old" => Arg::Old,
-        constant => Arg::Constant(constant.parse().unwrap()),
-    };
-    match parts[4] {
-        "+" => Operation::Add(arg0, arg1),
-        "*" => Operation::Multiply(arg0, arg1),
-        _ => panic!("invalid operator"),
+impl<'a> Lines<'a> {
+    fn new(contents: &'a str) -> Lines<'a> {
+        Lines {
+            lines: contents.lines().enumerate().peekable(),
+            last: 0,
+        }
+    }
+
+    fn is_finished(&mut self) -> bool {
+        while self
+            .lines
+            .next_if(|(_, line)| line.trim().is_empty())
+            .is_some()
+        {}
+        self.lines.peek().is_none()
+    }
+
+    fn field(&mut self, key: &str, phrase: &str) -> Result<(usize, &'a str), ParseError> {
+        if self.is_finished() {
+            return Err(error(
+                self.last + 1,
+                format!("unexpected end of input, expected '{}'", key),
+            ));
+        }
+        let (index, line) = self.lines.next().unwrap();
+        self.last = index + 1;
+        line.trim()
+            .strip_prefix(key)
+            .map(|rest| rest.trim_start())
+            .and_then(|rest| rest.strip_prefix(phrase))
+            .map(|rest| (self.last, rest.trim()))
+            .ok_or_else(|| {
+                let expected = format!("{} {}", key, phrase);
+                error(self.last, format!("expected '{}'", expected.trim()))
+            })
     }
 }
 
-fn parse_test(lines: &[&str]) -> Test {
-    (get_last(lines[0]), get_last(lines[1]), get_last(lines[2]))
+fn number<T: FromStr>(line: usize, text: &str, what: &str) -> Result<T, ParseError> {
+    text.parse()
+        .map_err(|_| error(line, format!("invalid {} '{}'", what, text)))
 }
 
-fn to_monkey(lines: &[&str]) -> Monkey {
-    Monkey {
-        items: parse_items(lines[1]),
-        operation: parse_op(lines[2]),
-        test: parse_test(&lines[3..6]),
-        inspection_count: 0,
+fn parse_rules(contents: &str) -> Result<Vec<Rule>, ParseError> {
+    let mut lines = Lines::new(contents);
+    let mut rules = vec![];
+    let mut targets = vec![];
+    while rules.is_empty() || !lines.is_finished() {
+        let (line, header) = lines.field("Monkey", "")?;
+        let id: usize = match header.strip_suffix(':') {
+            Some(id) => number(line, id.trim(), "monkey number")?,
+            None => return Err(error(line, "expected ':' after monkey number")),
+        };
+        if id != rules.len() {
+            return Err(error(
+                line,
+                format!("expected monkey {}, found {}", rules.len(), id),
+            ));
+        }
+
+        let (line, items) = lines.field("Starting items:", "")?;
+        let items = items
+            .split(',')
+            .map(|item| item.trim())
+            .filter(|item| !item.is_empty())
+            .map(|item| number(line, item, "worry level"))
+            .collect::<Result<Vec<i64>, ParseError>>()?;
+
+        let (line, source) = lines.field("Operation:", "new =")?;
+        let operation = Expression::parse(line, source)?;
+
+        let (line, divisor) = lines.field("Test:", "divisible by")?;
+        let divisor: i64 = number(line, divisor, "divisor")?;
+        if divisor <= 0 {
+            return Err(error(line, "divisor must be positive"));
+        }
+
+        let (line, if_true) = lines.field("If true:", "throw to monkey")?;
+        let if_true = number(line, if_true, "monkey number")?;
+        targets.push((line, if_true));
+        let (line, if_false) = lines.field("If false:", "throw to monkey")?;
+        let if_false = number(line, if_false, "monkey number")?;
+        targets.push((line, if_false));
+
+        rules.push(Rule {
+            items,
+            operation,
+            test: Test {
+                divisor,
+                if_true,
+                if_false,
+            },
+        });
+    }
+
+    match targets
+        .into_iter()
+        .find(|(_, target)| *target >= rules.len())
+    {
+        Some((line, target)) => Err(error(line, format!("there is no monkey {}", target))),
+        None => Ok(rules),
     }
 }
 
-fn parse_monkeys() -> Vec<Monkey> {
-    match read_to_string("data/day11.txt") {
-        Ok(contents) => {
-            let lines: Vec<&str> = contents.lines().collect();
-            lines.chunks(7).map(|x| to_monkey(&x[..6])).collect()
+impl<T: Integer> Item<T> {
+    fn describe(&self) -> String {
+        let path: Vec<String> = self.trace.iter().map(|m| m.to_string()).collect();
+        format!("item {} ({}): {}", self.id, self.worry, path.join(" -> "))
+    }
+}
+
+impl Trajectory {
+    fn simulated(&self) -> usize {
+        self.rounds.len() - 1
+    }
+
+    fn tally(&self, from: usize, to: usize, monkeys: usize) -> Vec<u64> {
+        let mut counts = vec![0; monkeys];
+        for monkey in &self.visits[self.rounds[from]..self.rounds[to]] {
+            counts[*monkey] += 1;
         }
-        _ => vec![],
+        counts
+    }
+
+    fn inspection_counts(
+        &self,
+        rounds: u64,
+        monkeys: usize,
+        ops: &Checked,
+    ) -> Result<Vec<u64>, Overflow> {
+        let start = match self.cycle_start {
+            Some(start) if rounds > self.simulated() as u64 => start,
+            _ => return Ok(self.tally(0, rounds as usize, monkeys)),
+        };
+        let period = (self.simulated() - start) as u64;
+        let (cycles, rest) = (
+            (rounds - start as u64) / period,
+            (rounds - start as u64) % period,
+        );
+        let prefix = self.tally(0, start + rest as usize, monkeys);
+        let cycle = self.tally(start, self.simulated(), monkeys);
+        prefix
+            .iter()
+            .zip(cycle)
+            .map(|(count, per_cycle)| ops.add(count, &ops.mul(&per_cycle, &cycles)?))
+            .collect()
     }
 }
 
-fn monkey_business(
-    mut monkeys: Vec<Monkey>,
-    num_rounds: usize,
-    worry_management: WorryManagement,
-) -> usize {
-    for _ in 0..num_rounds {
-        for i in 0..monkeys.len() {
-            inspect_and_throw(&mut monkeys, i, &worry_management)
+impl<'a, T: Integer> Simulation<'a, T> {
+    fn new(
+        rules: &'a [Rule],
+        worry_management: WorryManagement<T>,
+        tracing: bool,
+    ) -> Result<Simulation<'a, T>, Overflow> {
+        let ops = Checked::new("day11");
+        let mut id = 0;
+        let mut monkeys = vec![];
+        for (index, rule) in rules.iter().enumerate() {
+            let mut items = vec![];
+            for worry in &rule.items {
+                items.push(Item {
+                    id,
+                    worry: ops.convert(*worry)?,
+                    trace: vec![index],
+                });
+                id += 1;
+            }
+            monkeys.push(Monkey {
+                rule,
+                divisor: ops.convert(rule.test.divisor)?,
+                items,
+                inspection_count: 0,
+            });
+        }
+        Ok(Simulation {
+            monkeys,
+            worry_management,
+            tracing,
+            ops,
+        })
+    }
+
+    fn inspect(&self, index: usize, worry: &T) -> Result<(T, usize), Overflow> {
+        let ops = &self.ops;
+        let monkey = &self.monkeys[index];
+        let worry = monkey.rule.operation.evaluate(worry, ops)?;
+        let worry = match &self.worry_management {
+            WorryManagement::Decrease => ops.div(&worry, &ops.convert(3)?)?,
+            WorryManagement::Modulo(value) => ops.rem(&worry, value)?,
+            WorryManagement::Exact => worry,
+        };
+        let target = match ops.rem(&worry, &monkey.divisor)?.is_zero() {
+            true => monkey.rule.test.if_true,
+            false => monkey.rule.test.if_false,
+        };
+        Ok((worry, target))
+    }
+
+    fn turn(&mut self, index: usize) -> Result<(), Overflow> {
+        let items = mem::take(&mut self.monkeys[index].items);
+        self.monkeys[index].inspection_count += items.len();
+        for mut item in items {
+            let (worry, target) = self.inspect(index, &item.worry)?;
+            item.worry = worry;
+            if self.tracing {
+                item.trace.push(target);
+            }
+            self.monkeys[target].items.push(item);
         }
+        Ok(())
+    }
+
+    fn round(&mut self) -> Result<(), Overflow> {
+        (0..self.monkeys.len()).try_for_each(|index| self.turn(index))
+    }
+
+    fn run(&mut self, rounds: usize) -> Result<(), Overflow> {
+        (0..rounds).try_for_each(|_| self.round())
+    }
+
+    fn inspection_counts(&self) -> Vec<usize> {
+        self.monkeys.iter().map(|m| m.inspection_count).collect()
+    }
+
+    fn items(&self) -> Vec<&Item<T>> {
+        let mut items: Vec<&Item<T>> = self.monkeys.iter().flat_map(|m| &m.items).collect();
+        items.sort_by_key(|item| item.id);
+        items
+    }
+
+    fn monkey_business(&self) -> Result<usize, Overflow> {
+        monkey_business(self.inspection_counts(), &self.ops)
     }
-    monkeys.sort_by(|a, b| b.inspection_count.cmp(&a.inspection_count));
-    monkeys[0].inspection_count * monkeys[1].inspection_count
 }
 
-fn part1() -> usize {
-    let monkeys = parse_monkeys();
-    monkey_business(monkeys, 20, WorryManagement::Decrease)
+impl<'a, T: Integer + Hash> Simulation<'a, T> {
+    fn item_round(
+        &self,
+        (mut index, mut worry): (usize, T),
+        visits: &mut Vec<usize>,
+    ) -> Result<(usize, T), Overflow> {
+        loop {
+            visits.push(index);
+            let (next, target) = self.inspect(index, &worry)?;
+            worry = next;
+            if target <= index {
+                return Ok((target, worry));
+            }
+            index = target;
+        }
+    }
+
+    fn trajectory(&self, index: usize, worry: &T, limit: u64) -> Result<Trajectory, Overflow> {
+        let mut seen = HashMap::new();
+        let mut state = (index, worry.clone());
+        let mut trajectory = Trajectory {
+            visits: vec![],
+            rounds: vec![0],
+            cycle_start: None,
+        };
+        while (trajectory.simulated() as u64) < limit {
+            if let Some(start) = seen.insert(state.clone(), trajectory.simulated()) {
+                trajectory.cycle_start = Some(start);
+                break;
+            }
+            state = self.item_round(state, &mut trajectory.visits)?;
+            trajectory.rounds.push(trajectory.visits.len());
+        }
+        Ok(trajectory)
+    }
+
+    fn extrapolate(&self, rounds: u64) -> Result<Vec<u64>, Overflow> {
+        let mut counts = vec![0; self.monkeys.len()];
+        for (index, monkey) in self.monkeys.iter().enumerate() {
+            for item in &monkey.items {
+                let trajectory = self.trajectory(index, &item.worry, rounds)?;
+                let item_counts = trajectory.inspection_counts(rounds, counts.len(), &self.ops)?;
+                for (count, item_count) in counts.iter_mut().zip(item_counts) {
+                    *count = self.ops.add(count, &item_count)?;
+                }
+            }
+        }
+        Ok(counts)
+    }
 }
 
-fn part2() -> usize {
-    let monkeys = parse_monkeys();
-    let modulo = monkeys
+fn monkey_business<C: Integer>(mut counts: Vec<C>, ops: &Checked) -> Result<C, Overflow> {
+    counts.sort_by_key(|count| Reverse(count.clone()));
+    counts
         .iter()
-        .map(|m| m.test.0)
-        .reduce(|a, b| a * b)
-        .unwrap();
-    monkey_business(monkeys, 10000, WorryManagement::Modulo(modulo))
+        .take(2)
+        .try_fold(C::one(), |product, count| ops.mul(&product, count))
+}
+
+fn reduction_modulus<T: Integer>(rules: &[Rule], ops: &Checked) -> Result<Option<T>, Overflow> {
+    if !rules.iter().all(|r| r.operation.preserves_congruence()) {
+        return Ok(None);
+    }
+    rules.iter().try_fold(Some(T::one()), |modulus, rule| {
+        let modulus = modulus.unwrap();
+        let divisor: T = ops.convert(rule.test.divisor)?;
+        let result = checked_lcm(modulus.clone(), divisor.clone());
+        ops.check(Op::Multiply, &modulus, &divisor, result)
+            .map(Some)
+    })
+}
+
+fn part1<T: Integer>(rules: &[Rule]) -> Result<usize, Overflow> {
+    let mut simulation = Simulation::<T>::new(rules, WorryManagement::Decrease, false)?;
+    simulation.run(20)?;
+    simulation.monkey_business()
+}
+
+#[allow(dead_code)]
+fn trace<T: Integer>(rules: &[Rule], rounds: usize, id: usize) -> Result<String, Overflow> {
+    let mut simulation = Simulation::<T>::new(rules, WorryManagement::Decrease, true)?;
+    simulation.run(rounds)?;
+    Ok(match simulation.items().get(id) {
+        Some(item) => item.describe(),
+        None => format!("no item {}", id),
+    })
+}
+
+fn part2<T: Integer + Hash>(rules: &[Rule], rounds: u64) -> Result<i128, Overflow> {
+    let ops = Checked::new("day11");
+    let worry_management = match reduction_modulus(rules, &ops)? {
+        Some(modulus) => WorryManagement::Modulo(modulus),
+        None => WorryManagement::Exact,
+    };
+    let simulation = Simulation::<T>::new(rules, worry_management, false)?;
+    let counts = simulation.extrapolate(rounds)?;
+    monkey_business(counts.into_iter().map(i128::from).collect(), &ops)
 }
 
 pub fn run() {
+    let contents = read_to_string("data/day11.txt").unwrap_or_default();
     println!("== Day 11 ==");
-    println!("Part 1: {}", part1());
-    println!("Part 2: {}", part2())
+    let rules = match parse_rules(&contents) {
+        Ok(rules) => rules,
+        Err(error) => {
+            println!("Error: {}", error);
+            return;
+        }
+    };
+    let answer = with_fallback(
+        || part1::<i64>(&rules),
+        || with_fallback(|| part1::<i128>(&rules), || part1::<BigInt>(&rules)),
+    );
+    match answer {
+        Ok(value) => println!("Part 1: {}", value),
+        Err(error) => println!("Part 1 error: {}", error),
+    }
+    let answer = with_fallback(
+        || part2::<i64>(&rules, 10000),
+        || {
+            with_fallback(
+                || part2::<i128>(&rules, 10000),
+                || part2::<BigInt>(&rules, 10000),
+            )
+        },
+    );
+    match answer {
+        Ok(value) => println!("Part 2: {}", value),
+        Err(error) => println!("Part 2 error: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
+
+    #[test]
+    fn example() {
+        let rules = parse_rules(EXAMPLE).unwrap();
+        assert_eq!(part1::<i64>(&rules).ok(), Some(10605));
+        assert_eq!(part1::<BigInt>(&rules).ok(), Some(10605));
+        assert_eq!(part2::<i64>(&rules, 10000).ok(), Some(2713310158));
+        assert_eq!(part2::<i64>(&rules, 20).ok(), Some(99 * 103));
+    }
+
+    #[test]
+    fn long_runs_are_extrapolated() {
+        const LONG_RUN: u64 = 1_000_000_000_000;
+        let rules = parse_rules(EXAMPLE).unwrap();
+        let value = part2::<i64>(&rules, LONG_RUN).ok().unwrap();
+        assert_eq!(part2::<i128>(&rules, LONG_RUN).ok(), Some(value));
+        assert!(value > 2713310158 * 10_000_000_000_000);
+    }
+
+    #[test]
+    fn traces_an_item_path() {
+        let rules = parse_rules(EXAMPLE).unwrap();
+        assert_eq!(
+            trace::<i64>(&rules, 1, 0).ok().unwrap(),
+            "item 0 (167): 0 -> 3 -> 1"
+        );
+        assert_eq!(
+            trace::<i64>(&rules, 2, 0).ok().unwrap(),
+            "item 0 (362): 0 -> 3 -> 1 -> 2 -> 3 -> 1"
+        );
+        assert_eq!(trace::<i64>(&rules, 1, 10).ok().unwrap(), "no item 10");
+    }
+
+    #[test]
+    fn extrapolation_matches_simulation() {
+        let rules = parse_rules(EXAMPLE).unwrap();
+        let modulus = reduction_modulus::<i64>(&rules, &Checked::new("day11"))
+            .ok()
+            .unwrap()
+            .unwrap();
+        assert_eq!(modulus, 23 * 19 * 13 * 17);
+        let mut simulation =
+            Simulation::<i64>::new(&rules, WorryManagement::Modulo(modulus), false)
+                .ok()
+                .unwrap();
+        let counts = simulation.extrapolate(1000).ok().unwrap();
+        simulation.run(1000).ok().unwrap();
+        let simulated: Vec<u64> = simulation
+            .inspection_counts()
+            .iter()
+            .map(|count| *count as u64)
+            .collect();
+        assert_eq!(counts, simulated);
+    }
 }
@@ -1,87 +1,250 @@
-use crate::utils::{astar_search, read_to_vec, SearchInfo};
+use crate::utils::{read_to_vec, shortest_distances, SearchInfo};
 
 use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
 
 type Cell = (usize, usize);
 
-const MAX: usize = 25;
-const MIN: usize = 0;
-const INFINITY: usize = usize::MAX;
+const CARDINAL: [(i64, i64); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const DIAGONAL: [(i64, i64); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
 
 #[derive(Copy, Clone)]
-enum Square {
-    Start,
-    End,
-    Ground(usize),
-}
-
-impl Square {
-    fn can_reach(&self, other: &Square) -> bool {
-        match [*other, *self] {
-            [Square::Start, Square::Ground(height)] => height <= MIN + 1,
-            [_, Square::Start] => true,
-            [Square::Ground(h0), Square::End] => MAX <= h0 + 1,
-            [Square::Ground(h0), Square::Ground(h1)] => h1 <= h0 + 1,
-            [Square::End, _] => true,
-            [Square::Start, Square::End] => false,
-        }
-    }
+struct ClimbRules {
+    max_ascent: Option<i32>,
+    max_descent: Option<i32>,
+    diagonal: bool,
+    cost: fn(i32) -> usize,
 }
 
-struct Map {
-    squares: Vec<Vec<Square>>,
+struct Terrain {
+    heights: Vec<Vec<i32>>,
     rows: usize,
     cols: usize,
-    start: Cell,
-    end: Cell,
+    origin: Cell,
+    summit: Cell,
+}
+
+struct Descent<'a> {
+    terrain: &'a Terrain,
+    rules: &'a ClimbRules,
+}
+
+struct DistanceField {
+    distances: HashMap<Cell, usize>,
+    next: HashMap<Cell, Cell>,
 }
 
-impl Map {
-    fn to_neighbor(&self, square: &Cell, other: Cell) -> Option<Cell> {
-        let neighbor = match [*square, other] {
-            [(r0, c0), (r1, c1)] if self.squares[r0][c0].can_reach(&self.squares[r1][c1]) => {
-                Some(other)
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum TerrainError {
+    Empty,
+    InvalidSquare(usize, char),
+    Ragged(usize),
+    Duplicate(usize, char),
+    Missing(char),
+}
+
+impl Display for TerrainError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TerrainError::Empty => write!(f, "map is empty"),
+            TerrainError::InvalidSquare(line, square) => {
+                write!(f, "line {}: invalid square '{}'", line, square)
             }
-            _ => None,
-        };
-        neighbor
-    }
-}
-
-impl SearchInfo<Cell, usize> for Map {
-    type Data = usize;
-    fn neighbors(&self, square: &Cell, _: &usize) -> Vec<Cell> {
-        let r = square.0 as i32;
-        let c = square.1 as i32;
-        [(r - 1, c), (r + 1, c), (r, c - 1), (r, c + 1)]
-            .map(|c| match c {
-                (-1, _) | (_, -1) => None,
-                (r, _) if r as usize == self.rows => None,
-                (_, c) if c as usize == self.cols => None,
-                (r, c) => Some((r as usize, c as usize)),
-            })
+            TerrainError::Ragged(line) => write!(f, "line {}: row has a different width", line),
+            TerrainError::Duplicate(line, square) => {
+                write!(f, "line {}: more than one '{}'", line, square)
+            }
+            TerrainError::Missing(square) => write!(f, "no '{}' on the map", square),
+        }
+    }
+}
+
+fn elevation(square: char) -> Option<i32> {
+    match square {
+        'S' => elevation('a'),
+        'E' => elevation('z'),
+        'a'..='z' => Some(square as i32 - 'a' as i32),
+        _ => None,
+    }
+}
+
+#[allow(dead_code)]
+fn arrow((r0, c0): Cell, (r1, c1): Cell) -> char {
+    match (r1 as i64 - r0 as i64, c1 as i64 - c0 as i64) {
+        (-1, 0) => '^',
+        (1, 0) => 'v',
+        (0, -1) => '<',
+        (0, 1) => '>',
+        (-1, -1) | (1, 1) => '\\',
+        _ => '/',
+    }
+}
+
+impl ClimbRules {
+    fn standard() -> ClimbRules {
+        ClimbRules {
+            max_ascent: Some(1),
+            max_descent: None,
+            diagonal: false,
+            cost: |_| 1,
+        }
+    }
+
+    fn allows(&self, from: i32, to: i32) -> bool {
+        self.max_ascent.is_none_or(|ascent| to - from <= ascent)
+            && self.max_descent.is_none_or(|descent| from - to <= descent)
+    }
+
+    fn steps(&self) -> Vec<(i64, i64)> {
+        match self.diagonal {
+            true => CARDINAL.iter().chain(DIAGONAL.iter()).copied().collect(),
+            false => CARDINAL.to_vec(),
+        }
+    }
+}
+
+impl Terrain {
+    fn parse(lines: &[String]) -> Result<Terrain, TerrainError> {
+        let mut heights = vec![];
+        let (mut start, mut summit) = (None, None);
+        for (i, line) in lines.iter().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let r = heights.len();
+            let mut row = vec![];
+            for (c, square) in line.chars().enumerate() {
+                let terminal = match square {
+                    'S' => Some(&mut start),
+                    'E' => Some(&mut summit),
+                    _ => None,
+                };
+                if let Some(terminal) = terminal {
+                    if terminal.replace((r, c)).is_some() {
+                        return Err(TerrainError::Duplicate(i + 1, square));
+                    }
+                }
+                row.push(elevation(square).ok_or(TerrainError::InvalidSquare(i + 1, square))?);
+            }
+            if heights
+                .first()
+                .is_some_and(|first: &Vec<i32>| first.len() != row.len())
+            {
+                return Err(TerrainError::Ragged(i + 1));
+            }
+            heights.push(row);
+        }
+        if heights.is_empty() {
+            return Err(TerrainError::Empty);
+        }
+        Ok(Terrain {
+            rows: heights.len(),
+            cols: heights[0].len(),
+            heights,
+            origin: start.ok_or(TerrainError::Missing('S'))?,
+            summit: summit.ok_or(TerrainError::Missing('E'))?,
+        })
+    }
+
+    fn height(&self, (r, c): Cell) -> i32 {
+        self.heights[r][c]
+    }
+
+    fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        (0..self.rows).flat_map(move |r| (0..self.cols).map(move |c| (r, c)))
+    }
+
+    fn adjacent(&self, (r, c): Cell, rules: &ClimbRules) -> Vec<Cell> {
+        rules
+            .steps()
+            .into_iter()
+            .map(|(dr, dc)| (r as i64 + dr, c as i64 + dc))
+            .filter(|(r, c)| (0..self.rows as i64).contains(r) && (0..self.cols as i64).contains(c))
+            .map(|(r, c)| (r as usize, c as usize))
+            .collect()
+    }
+
+    fn distance_field(&self, rules: &ClimbRules) -> DistanceField {
+        let (distances, next) = shortest_distances(&Descent {
+            terrain: self,
+            rules,
+        });
+        DistanceField { distances, next }
+    }
+
+    #[allow(dead_code)]
+    fn render(&self, route: &[Cell]) -> String {
+        let mut grid: Vec<Vec<char>> = self
+            .heights
+            .iter()
+            .map(|row| row.iter().map(|h| (b'a' + *h as u8) as char).collect())
+            .collect();
+        for step in route.windows(2) {
+            grid[step[0].0][step[0].1] = arrow(step[0], step[1]);
+        }
+        grid[self.origin.0][self.origin.1] = 'S';
+        grid[self.summit.0][self.summit.1] = 'E';
+        grid.iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[allow(dead_code)]
+    fn to_ppm(&self, route: &[Cell]) -> String {
+        let highest = self
+            .cells()
+            .map(|cell| self.height(cell))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let mut ppm = format!("P3\n{} {}\n255\n", self.cols, self.rows);
+        for r in 0..self.rows {
+            let pixels: Vec<String> = (0..self.cols)
+                .map(|c| match (r, c) {
+                    cell if cell == self.origin => "0 255 0".to_string(),
+                    cell if cell == self.summit => "0 0 255".to_string(),
+                    cell if route.contains(&cell) => "255 0 0".to_string(),
+                    cell => {
+                        let level = self.height(cell) * 255 / highest;
+                        format!("{} {} {}", level, level, level)
+                    }
+                })
+                .collect();
+            ppm.push_str(&pixels.join(" "));
+            ppm.push('\n');
+        }
+        ppm
+    }
+}
+
+impl SearchInfo<Cell, usize> for Descent<'_> {
+    type Data = ();
+
+    fn neighbors(&self, cell: &Cell, _: &()) -> Vec<Cell> {
+        let height = self.terrain.height(*cell);
+        self.terrain
+            .adjacent(*cell, self.rules)
             .into_iter()
-            .filter_map(|c| c)
-            .filter_map(|c| self.to_neighbor(square, c))
+            .filter(|from| self.rules.allows(self.terrain.height(*from), height))
             .collect()
     }
 
-    fn distance(&self, lhs: &Cell, rhs: &Cell) -> usize {
-        let (r0, c0) = (lhs.0 as i32, lhs.1 as i32);
-        let (r1, c1) = (rhs.0 as i32, rhs.1 as i32);
-        ((r0 - r1).abs() + (c0 - c1).abs()) as usize
+    fn distance(&self, start: &Cell, end: &Cell) -> usize {
+        (self.rules.cost)(self.terrain.height(*start) - self.terrain.height(*end))
     }
 
-    fn heuristic(&self, node: &Cell) -> usize {
-        self.distance(node, &self.end)
+    fn heuristic(&self, _: &Cell) -> usize {
+        0
     }
 
     fn start(&self) -> Cell {
-        self.start
+        self.terrain.summit
     }
 
-    fn is_goal(&self, cell: &Cell) -> bool {
-        self.end.0 == cell.0 && self.end.1 == cell.1
+    fn is_goal(&self, _: &Cell) -> bool {
+        false
     }
 
     fn infinity() -> usize {
@@ -93,85 +256,169 @@ impl SearchInfo<Cell, usize> for Map {
     }
 }
 
-fn to_squares(line: &str) -> Vec<Square> {
-    const BASE: usize = 'a' as usize;
-    line.trim()
-        .chars()
-        .map(|c| match c {
-            'S' => Square::Start,
-            'E' => Square::End,
-            _ => Square::Ground((c as usize) - BASE),
-        })
-        .collect()
-}
-
-fn find_terminal(squares: &Vec<Vec<Square>>, square: Square) -> (usize, usize) {
-    squares
-        .iter()
-        .enumerate()
-        .flat_map(|(r, row)| {
-            row.iter()
-                .enumerate()
-                .map(move |(c, col)| match (col, square) {
-                    (Square::Start, Square::Start) => Some((r, c)),
-                    (Square::End, Square::End) => Some((r, c)),
-                    _ => None,
-                })
-        })
-        .filter_map(|x| x)
-        .last()
-        .unwrap()
-}
-
-fn parse_map() -> Map {
-    let squares: Vec<Vec<Square>> = read_to_vec("data/day12.txt", to_squares);
-    let rows = squares.len();
-    let cols = squares[0].len();
-    let start = find_terminal(&squares, Square::End);
-    let end = find_terminal(&squares, Square::Start);
-    Map {
-        squares,
-        rows,
-        cols,
-        start,
-        end,
+impl DistanceField {
+    fn distance(&self, cell: Cell) -> Option<usize> {
+        self.distances.get(&cell).copied()
     }
-}
 
-fn path_length(prev: &HashMap<Cell, Cell>, start: Cell, end: Cell) -> usize {
-    let mut length = 0usize;
-    if !prev.contains_key(&end) {
-        return INFINITY;
+    #[allow(dead_code)]
+    fn reachable(&self) -> usize {
+        self.distances.len()
     }
-    let mut current = end;
-    while current != start {
-        length += 1;
-        current = prev[&current];
+
+    #[allow(dead_code)]
+    fn route(&self, from: Cell) -> Option<Vec<Cell>> {
+        self.distance(from)?;
+        let mut route = vec![from];
+        let mut current = from;
+        while let Some(next) = self.next.get(&current) {
+            route.push(*next);
+            current = *next;
+        }
+        Some(route)
     }
-    length
 }
 
-fn part1(map: &Map, prev: &HashMap<Cell, Cell>) -> usize {
-    path_length(prev, map.start, map.end)
+fn part1(terrain: &Terrain, field: &DistanceField) -> Option<usize> {
+    field.distance(terrain.origin)
 }
 
-fn part2(map: &Map, prev: &HashMap<Cell, Cell>) -> usize {
-    (0..map.rows)
-        .into_iter()
-        .flat_map(|r| (0..map.cols).into_iter().map(move |c| (r, c)))
-        .into_iter()
-        .filter_map(|(r, c)| match map.squares[r][c] {
-            Square::Ground(0) | Square::Start => Some(path_length(prev, map.start, (r, c))),
-            _ => None,
-        })
+fn part2(terrain: &Terrain, field: &DistanceField) -> Option<usize> {
+    terrain
+        .cells()
+        .filter(|cell| terrain.height(*cell) == 0)
+        .filter_map(|cell| field.distance(cell))
         .min()
-        .unwrap()
 }
 
 pub fn run() {
-    let map = parse_map();
-    let prev = astar_search(&map).unwrap().0;
+    let lines = read_to_vec("data/day12.txt", |line| line.to_string());
     println!("== Day 12 ==");
-    println!("Part 1: {}", part1(&map, &prev));
-    println!("Part 2: {}", part2(&map, &prev))
+    let terrain = match Terrain::parse(&lines) {
+        Ok(terrain) => terrain,
+        Err(error) => {
+            println!("Error: {}", error);
+            return;
+        }
+    };
+    let field = terrain.distance_field(&ClimbRules::standard());
+    match part1(&terrain, &field) {
+        Some(steps) => println!("Part 1: {}", steps),
+        None => println!("Part 1 error: the summit cannot be reached from the start"),
+    }
+    match part2(&terrain, &field) {
+        Some(steps) => println!("Part 2: {}", steps),
+        None => println!("Part 2 error: the summit cannot be reached from the lowest squares"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terrain(lines: &[&str]) -> Result<Terrain, TerrainError> {
+        let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        Terrain::parse(&lines)
+    }
+
+    fn climb(terrain: &Terrain, rules: &ClimbRules) -> Option<usize> {
+        part1(terrain, &terrain.distance_field(rules))
+    }
+
+    #[test]
+    fn example() {
+        let terrain =
+            terrain(&["Sabqponm", "abcryxxl", "accszExk", "acctuvwj", "abdefghi"]).unwrap();
+        let field = terrain.distance_field(&ClimbRules::standard());
+        assert_eq!(part1(&terrain, &field), Some(31));
+        assert_eq!(part2(&terrain, &field), Some(29));
+        assert_eq!(field.reachable(), 40);
+    }
+
+    #[test]
+    fn route_overlay_and_ppm() {
+        let terrain =
+            terrain(&["Sabqponm", "abcryxxl", "accszExk", "acctuvwj", "abdefghi"]).unwrap();
+        let field = terrain.distance_field(&ClimbRules::standard());
+        let route = field.route(terrain.origin).unwrap();
+        assert_eq!(route.len(), 32);
+        assert_eq!(route.last(), Some(&terrain.summit));
+        assert_eq!(
+            terrain.render(&route),
+            "Sabv<<<<\n>>vvv<<^\nacvv>E^^\nacv>>>^^\nab>>>>>^"
+        );
+        let ppm = terrain.to_ppm(&route);
+        let lines: Vec<&str> = ppm.lines().collect();
+        assert_eq!(lines[..3], ["P3", "8 5", "255"]);
+        assert_eq!(
+            lines[3],
+            "0 255 0 0 0 0 10 10 10 255 0 0 255 0 0 255 0 0 255 0 0 255 0 0"
+        );
+        assert_eq!(
+            lines[5],
+            "0 0 0 20 20 20 255 0 0 255 0 0 255 0 0 0 0 255 255 0 0 255 0 0"
+        );
+
+        let terrain = self::terrain(&["SazE"]).unwrap();
+        let field = terrain.distance_field(&ClimbRules::standard());
+        assert_eq!(field.route(terrain.origin), None);
+        assert_eq!(field.route((0, 2)), Some(vec![(0, 2), (0, 3)]));
+        assert_eq!(terrain.render(&[(0, 2), (0, 3)]), "Sa>E");
+    }
+
+    #[test]
+    fn ascent_and_descent_limits_apply_in_the_walking_direction() {
+        let terrain = terrain(&["SdbE"]).unwrap();
+        let mut rules = ClimbRules {
+            max_ascent: None,
+            max_descent: Some(1),
+            ..ClimbRules::standard()
+        };
+        assert_eq!(climb(&terrain, &rules), None);
+        rules.max_descent = Some(2);
+        assert_eq!(climb(&terrain, &rules), Some(3));
+
+        let terrain = self::terrain(&["SbcdefghijklmnopqrstuvwxyE"]).unwrap();
+        rules.max_ascent = Some(1);
+        rules.max_descent = Some(0);
+        assert_eq!(climb(&terrain, &rules), Some(25));
+        rules.max_ascent = Some(0);
+        rules.max_descent = None;
+        assert_eq!(climb(&terrain, &rules), None);
+    }
+
+    #[test]
+    fn diagonal_steps_and_costs() {
+        let terrain = terrain(&["Sbz", "zbz", "zzE"]).unwrap();
+        assert_eq!(climb(&terrain, &ClimbRules::standard()), None);
+        let diagonal = ClimbRules {
+            diagonal: true,
+            max_ascent: None,
+            ..ClimbRules::standard()
+        };
+        assert_eq!(climb(&terrain, &diagonal), Some(2));
+        let costly = ClimbRules {
+            cost: |ascent| 1 + ascent.max(0) as usize,
+            ..diagonal
+        };
+        assert_eq!(climb(&terrain, &costly), Some(2 + 1 + 24));
+    }
+
+    #[test]
+    fn invalid_maps() {
+        assert!(matches!(terrain(&[]), Err(TerrainError::Empty)));
+        assert!(matches!(
+            terrain(&["Sa1E"]),
+            Err(TerrainError::InvalidSquare(1, '1'))
+        ));
+        assert!(matches!(
+            terrain(&["SaE", "ab"]),
+            Err(TerrainError::Ragged(2))
+        ));
+        assert!(matches!(
+            terrain(&["SaS", "abE"]),
+            Err(TerrainError::Duplicate(1, 'S'))
+        ));
+        assert!(matches!(terrain(&["aaE"]), Err(TerrainError::Missing('S'))));
+    }
 }
@@ -1,12 +1,36 @@
-use crate::utils::read_some_to_vec;
 use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::fs::read_to_string;
+use std::slice;
+use std::str::FromStr;
 
-#[derive(Eq, PartialEq)]
+#[derive(Debug, Clone)]
 enum Packet {
-    Value(usize),
+    Value(i64),
     List(Vec<Packet>),
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum ParseErrorKind {
+    UnexpectedChar(char),
+    UnexpectedEnd,
+    InvalidNumber(String),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct ParseError {
+    position: usize,
+    kind: ParseErrorKind,
+}
+
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        compare(self, other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
 impl Ord for Packet {
     fn cmp(&self, other: &Self) -> Ordering {
         compare(self, other)
@@ -15,104 +39,249 @@ impl Ord for Packet {
 
 impl PartialOrd for Packet {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(compare(self, other))
+        Some(self.cmp(other))
     }
 }
 
 fn compare(left: &Packet, right: &Packet) -> Ordering {
-    match [left, right] {
-        [Packet::Value(lhs), Packet::Value(rhs)] => lhs.cmp(rhs),
-        [Packet::List(lhs), Packet::List(rhs)] => {
-            let count = if lhs.len() < rhs.len() {
-                lhs.len()
-            } else {
-                rhs.len()
-            };
-            for i in 0..count {
-                match compare(&lhs[i], &rhs[i]) {
-                    Ordering::Less => return Ordering::Less,
-                    Ordering::Greater => return Ordering::Greater,
-                    Ordering::Equal => {}
-                }
+    match (left, right) {
+        (Packet::Value(lhs), Packet::Value(rhs)) => lhs.cmp(rhs),
+        (Packet::List(lhs), Packet::List(rhs)) => compare_lists(lhs, rhs),
+        (Packet::List(lhs), Packet::Value(_)) => compare_lists(lhs, slice::from_ref(right)),
+        (Packet::Value(_), Packet::List(rhs)) => compare_lists(slice::from_ref(left), rhs),
+    }
+}
+
+fn compare_lists(left: &[Packet], right: &[Packet]) -> Ordering {
+    left.iter()
+        .zip(right)
+        .map(|(lhs, rhs)| compare(lhs, rhs))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| left.len().cmp(&right.len()))
+}
+
+mod ordering {
+    use super::{compare, Packet};
+    use std::cmp::{Ordering, Reverse};
+    use std::collections::BinaryHeap;
+
+    struct Head {
+        packet: Packet,
+        stream: usize,
+    }
+
+    impl PartialEq for Head {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl Eq for Head {}
+
+    impl Ord for Head {
+        fn cmp(&self, other: &Self) -> Ordering {
+            compare(&self.packet, &other.packet).then(self.stream.cmp(&other.stream))
+        }
+    }
+
+    impl PartialOrd for Head {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    pub fn sort(packets: &mut [Packet]) {
+        packets.sort_by(compare)
+    }
+
+    pub fn insertion_index(sorted: &[Packet], probe: &Packet) -> usize {
+        sorted.partition_point(|packet| compare(packet, probe).is_lt())
+    }
+
+    fn identical(left: &Packet, right: &Packet) -> bool {
+        match (left, right) {
+            (Packet::Value(lhs), Packet::Value(rhs)) => lhs == rhs,
+            (Packet::List(lhs), Packet::List(rhs)) => {
+                lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(l, r)| identical(l, r))
             }
-            if lhs.len() < rhs.len() {
-                Ordering::Less
-            } else if rhs.len() < lhs.len() {
-                Ordering::Greater
-            } else {
-                Ordering::Equal
+            _ => false,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn dedup(sorted: &mut Vec<Packet>) {
+        let mut kept: Vec<Packet> = vec![];
+        let mut run = 0;
+        for packet in sorted.drain(..) {
+            if kept
+                .last()
+                .is_none_or(|last| compare(last, &packet).is_ne())
+            {
+                run = kept.len();
+            }
+            if !kept[run..].iter().any(|k| identical(k, &packet)) {
+                kept.push(packet);
             }
         }
-        [Packet::List(lhs), Packet::Value(_)] => {
-            if lhs.is_empty() {
-                Ordering::Less
-            } else {
-                match compare(&lhs[0], right) {
-                    Ordering::Less => Ordering::Less,
-                    _ => Ordering::Greater,
-                }
+        *sorted = kept;
+    }
+
+    pub fn merge<I: Iterator<Item = Packet>>(streams: Vec<I>) -> Vec<Packet> {
+        let mut streams = streams;
+        let mut heap = BinaryHeap::new();
+        for (stream, iter) in streams.iter_mut().enumerate() {
+            if let Some(packet) = iter.next() {
+                heap.push(Reverse(Head { packet, stream }));
             }
         }
-        [Packet::Value(_), Packet::List(rhs)] => {
-            if rhs.is_empty() {
-                Ordering::Greater
-            } else {
-                match compare(left, &rhs[0]) {
-                    Ordering::Greater => Ordering::Greater,
-                    _ => Ordering::Less,
-                }
+        let mut merged = vec![];
+        while let Some(Reverse(head)) = heap.pop() {
+            if let Some(packet) = streams[head.stream].next() {
+                heap.push(Reverse(Head {
+                    packet,
+                    stream: head.stream,
+                }));
             }
+            merged.push(head.packet);
         }
+        merged
     }
 }
 
-fn next_token(input: &str) -> (Option<&str>, &str) {
-    match input.chars().next() {
-        Some('[') | Some(']') => (Some(&input[..1]), &input[1..]),
-        Some(',') => next_token(&input[1..]),
-        Some(c) if c.is_ascii_digit() => match input.find(|c: char| c == ',' || c == ']') {
-            Some(i) => (Some(&input[..i]), &input[i..]),
-            None => (Some(&input[..]), ""),
-        },
-        Some(_) => panic!("invalid char"),
-        None => (None, ""),
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "position {}: ", self.position)?;
+        match &self.kind {
+            ParseErrorKind::UnexpectedChar(c) => write!(f, "unexpected '{}'", c),
+            ParseErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ParseErrorKind::InvalidNumber(number) => write!(f, "invalid number '{}'", number),
+        }
     }
 }
 
-fn parse_packet(input: &str) -> (Packet, &str) {
-    let mut tokens = input;
-    let mut stack: Vec<Vec<Packet>> = vec![];
-    let mut current: Vec<Packet> = vec![];
-    while let (Some(token), remainder) = next_token(tokens) {
-        tokens = remainder;
-        match token {
-            "[" => {
-                stack.push(current);
-                current = vec![];
-            }
-            "]" => {
-                let packet = Packet::List(current);
-                current = stack.pop().unwrap();
-                current.push(packet)
+impl Display for Packet {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Packet::Value(value) => write!(f, "{}", value),
+            Packet::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
             }
-            value => current.push(Packet::Value(value.parse().unwrap())),
         }
     }
-    (Packet::List(current), tokens)
 }
 
-fn to_packet(line: &str) -> Option<Packet> {
-    match line.trim() {
-        "" => None,
-        input => Some(parse_packet(input).0),
+fn is_number_char(c: u8) -> bool {
+    c.is_ascii_digit() || matches!(c, b'-' | b'+' | b'.' | b'e' | b'E')
+}
+
+fn parse_number(text: &str, position: usize) -> Result<Packet, ParseError> {
+    let error = || ParseError {
+        position,
+        kind: ParseErrorKind::InvalidNumber(text.to_string()),
+    };
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    if digits.is_empty()
+        || !digits.bytes().all(|c| c.is_ascii_digit())
+        || (digits.len() > 1 && digits.starts_with('0'))
+    {
+        return Err(error());
     }
+    text.parse().map(Packet::Value).map_err(|_| error())
 }
 
-fn read_packets() -> Vec<Packet> {
-    read_some_to_vec("data/day13.txt", to_packet)
+impl FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Packet, ParseError> {
+        let bytes = input.as_bytes();
+        let error = |position: usize, kind| ParseError { position, kind };
+        let unexpected = |position: usize| match input[position..].chars().next() {
+            Some(c) => error(position, ParseErrorKind::UnexpectedChar(c)),
+            None => error(position, ParseErrorKind::UnexpectedEnd),
+        };
+        let skip_whitespace = |mut position: usize| {
+            while position < bytes.len() && bytes[position].is_ascii_whitespace() {
+                position += 1;
+            }
+            position
+        };
+
+        let mut stack: Vec<Vec<Packet>> = vec![];
+        let mut position = skip_whitespace(0);
+        let packet = loop {
+            let value = match bytes.get(position) {
+                Some(b'[') => {
+                    position = skip_whitespace(position + 1);
+                    if bytes.get(position) == Some(&b']') {
+                        position += 1;
+                        Packet::List(vec![])
+                    } else {
+                        stack.push(vec![]);
+                        continue;
+                    }
+                }
+                Some(c) if is_number_char(*c) => {
+                    let start = position;
+                    while position < bytes.len() && is_number_char(bytes[position]) {
+                        position += 1;
+                    }
+                    parse_number(&input[start..position], start)?
+                }
+                _ => return Err(unexpected(position)),
+            };
+
+            let mut value = value;
+            let finished = loop {
+                position = skip_whitespace(position);
+                match stack.last_mut() {
+                    Some(items) => items.push(value),
+                    None => break Some(value),
+                }
+                match bytes.get(position) {
+                    Some(b',') => {
+                        position = skip_whitespace(position + 1);
+                        break None;
+                    }
+                    Some(b']') => {
+                        position += 1;
+                        value = Packet::List(stack.pop().unwrap());
+                    }
+                    _ => return Err(unexpected(position)),
+                }
+            };
+            if let Some(packet) = finished {
+                break packet;
+            }
+        };
+
+        match position {
+            end if end == bytes.len() => Ok(packet),
+            _ => Err(unexpected(position)),
+        }
+    }
 }
 
-fn part1(packets: &Vec<Packet>) -> usize {
+fn read_packets(filename: &str) -> Result<Vec<Packet>, String> {
+    let contents = read_to_string(filename).unwrap_or_default();
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            line.parse()
+                .map_err(|error| format!("line {}, {}", i + 1, error))
+        })
+        .collect()
+}
+
+fn part1(packets: &[Packet]) -> usize {
     packets
         .chunks(2)
         .enumerate()
@@ -123,26 +292,230 @@ fn part1(packets: &Vec<Packet>) -> usize {
         .sum()
 }
 
-fn part2(packets: &Vec<Packet>) -> usize {
-    let dividers = vec![parse_packet("[[[2]]").0, parse_packet("[[6]]").0];
-    let mut all_packets = vec![];
-    all_packets.extend(packets);
-    all_packets.extend(&dividers);
-    all_packets.sort();
-    all_packets
-        .into_iter()
-        .enumerate()
-        .map(|(i, p)| match p {
-            p if p == &dividers[0] || p == &dividers[1] => i + 1,
-            _ => 1,
+fn part2(packets: &[Packet]) -> usize {
+    let pairs = packets
+        .chunks(2)
+        .map(|pair| {
+            let mut pair = pair.to_vec();
+            ordering::sort(&mut pair);
+            pair.into_iter()
         })
-        .reduce(|a, b| a * b)
-        .unwrap()
+        .collect();
+    let sorted = ordering::merge(pairs);
+    ["[[2]]", "[[6]]"]
+        .iter()
+        .enumerate()
+        .map(|(i, divider)| ordering::insertion_index(&sorted, &divider.parse().unwrap()) + i + 1)
+        .product()
 }
 
 pub fn run() {
-    let packets = read_packets();
     println!("== Day 13 ==");
+    let packets = match read_packets("data/day13.txt") {
+        Ok(packets) => packets,
+        Err(error) => {
+            println!("Error: {}", error);
+            return;
+        }
+    };
     println!("Part 1: {}", part1(&packets));
     println!("Part 2: {}", part2(&packets))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Generator(u64);
+
+    impl Generator {
+        fn next(&mut self, bound: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % bound
+        }
+
+        fn packet(&mut self, depth: usize) -> Packet {
+            match self.next(3) {
+                0 if depth > 0 => {
+                    let len = self.next(4) as usize;
+                    Packet::List((0..len).map(|_| self.packet(depth - 1)).collect())
+                }
+                1 if depth > 0 => Packet::List(vec![self.packet(depth - 1)]),
+                _ => Packet::Value(self.next(3) as i64),
+            }
+        }
+
+        fn packets(&mut self, count: usize) -> Vec<Packet> {
+            (0..count).map(|_| self.packet(3)).collect()
+        }
+    }
+
+    fn render(packets: &[Packet]) -> Vec<String> {
+        packets.iter().map(|packet| packet.to_string()).collect()
+    }
+
+    fn parse(packets: &[&str]) -> Vec<Packet> {
+        packets
+            .iter()
+            .map(|packet| packet.parse().unwrap())
+            .collect()
+    }
+
+    fn parse_error(input: &str) -> ParseError {
+        input.parse::<Packet>().err().unwrap()
+    }
+
+    #[test]
+    fn parser_reports_error_positions() {
+        let error = |position, kind| ParseError { position, kind };
+        assert_eq!(
+            parse_error("[1,]"),
+            error(3, ParseErrorKind::UnexpectedChar(']'))
+        );
+        assert_eq!(parse_error("["), error(1, ParseErrorKind::UnexpectedEnd));
+        assert_eq!(parse_error(""), error(0, ParseErrorKind::UnexpectedEnd));
+        assert_eq!(
+            parse_error("[01]"),
+            error(1, ParseErrorKind::InvalidNumber("01".to_string()))
+        );
+        assert_eq!(
+            parse_error("[1,-]"),
+            error(3, ParseErrorKind::InvalidNumber("-".to_string()))
+        );
+        assert_eq!(
+            parse_error("[1.5]"),
+            error(1, ParseErrorKind::InvalidNumber("1.5".to_string()))
+        );
+        assert_eq!(
+            parse_error("[1] [2]"),
+            error(4, ParseErrorKind::UnexpectedChar('['))
+        );
+        assert_eq!(
+            parse_error("[1 2]"),
+            error(3, ParseErrorKind::UnexpectedChar('2'))
+        );
+        assert_eq!(
+            parse_error("[1,]").to_string(),
+            "position 3: unexpected ']'"
+        );
+    }
+
+    #[test]
+    fn parser_round_trips_through_display() {
+        for packet in [
+            "[-2]",
+            "[]",
+            "[[],[[]]]",
+            "7",
+            "[1,[2,[3,-40]],0]",
+            "[9223372036854775807]",
+        ] {
+            assert_eq!(packet.parse::<Packet>().unwrap().to_string(), packet);
+        }
+        assert_eq!(
+            " [ 1 ,\t[ ] , -3 ]\n"
+                .parse::<Packet>()
+                .unwrap()
+                .to_string(),
+            "[1,[],-3]"
+        );
+        assert!(compare(&parse(&["[-2]"])[0], &parse(&["[0]"])[0]).is_lt());
+    }
+
+    #[test]
+    fn compare_is_a_total_order() {
+        let packets = Generator(0x2545f4914f6cdd1d).packets(60);
+        for a in &packets {
+            assert!(compare(a, a).is_eq());
+            for b in &packets {
+                assert_eq!(compare(a, b), compare(b, a).reverse());
+                assert!(compare(a, b).is_le() || compare(b, a).is_le());
+                for c in &packets {
+                    if compare(a, b).is_le() && compare(b, c).is_le() {
+                        assert!(compare(a, c).is_le(), "{} <= {} <= {}", a, b, c);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn insertion_index_matches_linear_scan() {
+        let mut generator = Generator(0x9e3779b97f4a7c15);
+        let mut sorted = generator.packets(80);
+        ordering::sort(&mut sorted);
+        for probe in generator.packets(80) {
+            let smaller = sorted.iter().filter(|p| compare(p, &probe).is_lt()).count();
+            assert_eq!(ordering::insertion_index(&sorted, &probe), smaller);
+        }
+    }
+
+    #[test]
+    fn merge_is_sorted_and_stable() {
+        let mut generator = Generator(0xd1b54a32d192ed03);
+        let streams: Vec<Vec<Packet>> = (0..5)
+            .map(|i| {
+                let mut stream = generator.packets(i * 7);
+                ordering::sort(&mut stream);
+                stream
+            })
+            .collect();
+        let mut expected: Vec<Packet> = streams.concat();
+        ordering::sort(&mut expected);
+        let merged = ordering::merge(streams.into_iter().map(|s| s.into_iter()).collect());
+        assert!(merged
+            .windows(2)
+            .all(|pair| compare(&pair[0], &pair[1]).is_le()));
+        assert_eq!(render(&merged), render(&expected));
+    }
+
+    #[test]
+    fn dedup_keeps_structurally_distinct_packets() {
+        let mut packets = parse(&["[1]", "1", "[[1]]", "[1]", "1", "[]", "[2]", "[]"]);
+        ordering::sort(&mut packets);
+        ordering::dedup(&mut packets);
+        assert_eq!(render(&packets), ["[]", "[1]", "1", "[[1]]", "[2]"]);
+
+        let mut packets = Generator(0x853c49e6748fea9b).packets(200);
+        let original = render(&packets);
+        ordering::sort(&mut packets);
+        ordering::dedup(&mut packets);
+        let mut distinct = original.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(packets.len(), distinct.len());
+        assert!(packets
+            .windows(2)
+            .all(|pair| compare(&pair[0], &pair[1]).is_le()));
+    }
+
+    #[test]
+    fn example() {
+        let packets = parse(&[
+            "[1,1,3,1,1]",
+            "[1,1,5,1,1]",
+            "[[1],[2,3,4]]",
+            "[[1],4]",
+            "[9]",
+            "[[8,7,6]]",
+            "[[4,4],4,4]",
+            "[[4,4],4,4,4]",
+            "[7,7,7,7]",
+            "[7,7,7]",
+            "[]",
+            "[3]",
+            "[[[]]]",
+            "[[]]",
+            "[1,[2,[3,[4,[5,6,7]]]],8,9]",
+            "[1,[2,[3,[4,[5,6,0]]]],8,9]",
+        ]);
+        assert_eq!(part1(&packets), 13);
+        assert_eq!(part2(&packets), 140);
+        let mut sorted = packets.clone();
+        ordering::sort(&mut sorted);
+        ordering::dedup(&mut sorted);
+        assert_eq!(sorted.len(), 16);
+    }
+}
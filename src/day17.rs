@@ -2,7 +2,11 @@ use std::collections::HashSet;
 use std::fs::read_to_string;
 
 use crate::{
-    maths::Vec2,
+    maths::{
+        checked::{with_fallback, Checked, Overflow},
+        integer::Integer,
+        Vec2,
+    },
     utils::{find_next, is_match},
 };
 
@@ -197,7 +201,7 @@ fn part1() -> i32 {
     simulate_rocks(2022).height
 }
 
-fn part2() -> i64 {
+fn find_sequence() -> (Vec<i32>, usize, i32) {
     let levels = simulate_rocks(5000).to_bytes();
     const WINDOW_SIZE: usize = 10;
     let mut pattern_start = 0i32;
@@ -228,14 +232,41 @@ fn part2() -> i64 {
     }
 
     sequence.pop();
-    let sequence_length = sequence.len();
-    let tail_length = (1000000000000i64 - sequence_start as i64) % sequence_length as i64;
-    let num_sequences = (1000000000000i64 - sequence_start as i64) / sequence_length as i64;
-    sequence[tail_length as usize] as i64 + pattern_length as i64 * num_sequences
+    (sequence, sequence_start, pattern_length)
+}
+
+fn extrapolate<T: Integer>(
+    sequence: &[i32],
+    sequence_start: usize,
+    pattern_length: i32,
+) -> Result<T, Overflow> {
+    let ops = Checked::new("day17");
+    let remaining = ops.sub(
+        &ops.convert::<T>(1000000000000)?,
+        &ops.convert(sequence_start as i64)?,
+    )?;
+    let sequence_length = ops.convert(sequence.len() as i64)?;
+    let tail_length = ops.as_i64(&ops.rem(&remaining, &sequence_length)?)?;
+    let num_sequences = ops.div(&remaining, &sequence_length)?;
+    ops.add(
+        &ops.convert(sequence[tail_length as usize] as i64)?,
+        &ops.mul(&ops.convert(pattern_length as i64)?, &num_sequences)?,
+    )
+}
+
+fn part2() -> Result<String, Overflow> {
+    let (sequence, sequence_start, pattern_length) = find_sequence();
+    with_fallback(
+        || extrapolate::<i64>(&sequence, sequence_start, pattern_length),
+        || extrapolate::<i128>(&sequence, sequence_start, pattern_length),
+    )
 }
 
 pub fn run() {
     println!("== Day 17 ==");
     println!("Part 1: {}", part1());
-    println!("Part 2: {}", part2());
+    match part2() {
+        Ok(value) => println!("Part 2: {}", value),
+        Err(error) => println!("Part 2 error: {}", error),
+    }
 }
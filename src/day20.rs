@@ -1,27 +1,29 @@
+use crate::maths::checked::{with_fallback, Checked, Overflow};
+use crate::maths::integer::Integer;
 use crate::utils::read_to_vec;
 
-struct Node {
-    delta: i64,
+struct Node<T> {
+    delta: T,
     prev: usize,
     next: usize,
 }
 
-fn to_final(nodes: &Vec<Node>) -> Vec<i64> {
-    let mut result = vec![0];
-    let start = nodes.iter().position(|n| n.delta == 0).unwrap();
+fn to_final<T: Integer>(nodes: &[Node<T>]) -> Vec<T> {
+    let mut result = vec![T::zero()];
+    let start = nodes.iter().position(|n| n.delta.is_zero()).unwrap();
     let mut current = nodes[start].next;
     for _ in 1..nodes.len() {
-        result.push(nodes[current].delta);
+        result.push(nodes[current].delta.clone());
         current = nodes[current].next;
     }
 
     result
 }
 
-fn mix(nodes: &mut Vec<Node>) {
-    let count = nodes.len() as i64 - 1;
+fn mix<T: Integer>(nodes: &mut [Node<T>], ops: &Checked) -> Result<(), Overflow> {
+    let count = ops.convert::<T>(nodes.len() as i64 - 1)?;
     for n in 0..nodes.len() {
-        let delta = nodes[n].delta % count;
+        let delta = ops.as_i64(&ops.rem(&nodes[n].delta, &count)?)?;
         if delta == 0 {
             continue;
         }
@@ -47,60 +49,71 @@ fn mix(nodes: &mut Vec<Node>) {
         nodes[n].prev = prev;
         nodes[prev].next = n;
     }
+
+    Ok(())
 }
 
-fn part1() -> i64 {
-    let mut nodes = read_nodes();
-    mix(&mut nodes);
-    let mixed = to_final(&nodes);
+fn grove_coordinates<T: Integer>(nodes: &[Node<T>], ops: &Checked) -> Result<T, Overflow> {
+    let mixed = to_final(nodes);
     [1000, 2000, 3000]
         .into_iter()
-        .map(|i| mixed[i % mixed.len()])
-        .sum()
+        .try_fold(T::zero(), |sum, i| ops.add(&sum, &mixed[i % mixed.len()]))
+}
+
+fn part1<T: Integer>() -> Result<T, Overflow> {
+    let ops = Checked::new("day20");
+    let mut nodes = read_nodes::<T>(&ops)?;
+    mix(&mut nodes, &ops)?;
+    grove_coordinates(&nodes, &ops)
 }
 
-fn part2() -> i64 {
-    let mut nodes = read_nodes();
+fn part2<T: Integer>() -> Result<T, Overflow> {
+    let ops = Checked::new("day20");
+    let mut nodes = read_nodes::<T>(&ops)?;
+    let key = ops.convert::<T>(811589153)?;
     for n in nodes.iter_mut() {
-        n.delta *= 811589153;
+        n.delta = ops.mul(&n.delta, &key)?;
     }
 
     for _ in 0..10 {
-        mix(&mut nodes);
+        mix(&mut nodes, &ops)?;
     }
-    let mixed = to_final(&nodes);
-    [1000, 2000, 3000]
-        .into_iter()
-        .map(|i| mixed[i % mixed.len()])
-        .sum()
+    grove_coordinates(&nodes, &ops)
 }
 
-fn read_nodes() -> Vec<Node> {
+fn read_nodes<T: Integer>(ops: &Checked) -> Result<Vec<Node<T>>, Overflow> {
     let deltas = read_to_vec("data/day20.txt", |s| s.trim().parse::<i64>().unwrap());
     let mut nodes = deltas
         .into_iter()
-        .map(|d| Node {
-            delta: d,
-            prev: 0,
-            next: 0,
+        .map(|d| {
+            Ok(Node {
+                delta: ops.convert(d)?,
+                prev: 0,
+                next: 0,
+            })
         })
-        .collect::<Vec<Node>>();
+        .collect::<Result<Vec<Node<T>>, Overflow>>()?;
 
-    for i in 0..nodes.len() {
-        let (prev, next) = match i {
-            0 => (nodes.len() - 1, 1),
-            _ if i == nodes.len() - 1 => (nodes.len() - 2, 0),
+    let length = nodes.len();
+    for (i, node) in nodes.iter_mut().enumerate() {
+        (node.prev, node.next) = match i {
+            0 => (length - 1, 1),
+            _ if i == length - 1 => (length - 2, 0),
             _ => (i - 1, i + 1),
         };
-        nodes[i].prev = prev;
-        nodes[i].next = next;
     }
 
-    nodes
+    Ok(nodes)
 }
 
 pub fn run() {
     println!("== Day 20 ==");
-    println!("Part 1: {}", part1());
-    println!("Part 2: {}", part2())
+    match with_fallback(part1::<i64>, part1::<i128>) {
+        Ok(value) => println!("Part 1: {}", value),
+        Err(error) => println!("Part 1 error: {}", error),
+    }
+    match with_fallback(part2::<i64>, part2::<i128>) {
+        Ok(value) => println!("Part 2: {}", value),
+        Err(error) => println!("Part 2 error: {}", error),
+    }
 }
@@ -1,128 +1,605 @@
 use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
 
+use crate::maths::checked::{with_fallback, Checked, Op, Overflow};
+use crate::maths::integer::Integer;
+use crate::maths::polynomial::Polynomial;
+use crate::maths::rational::Rational;
 use crate::utils::read_to_vec;
 
 enum Expression {
     Literal(i64),
-    Add(String, String),
-    Subtract(String, String),
-    Multiply(String, String),
-    Divide(String, String),
+    Binary(Op, String, String),
+}
+
+enum Node {
+    Literal(i64),
+    Binary(Op, usize, usize),
+}
+
+struct Dag {
+    names: Vec<String>,
+    nodes: Vec<Node>,
+    order: Vec<usize>,
+    index: HashMap<String, usize>,
+}
+
+enum CompileError {
+    Missing(String),
+    Undefined(String, String),
+    Cycle(Vec<String>),
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            CompileError::Missing(name) => write!(f, "day21: no monkey named {}", name),
+            CompileError::Undefined(name, parent) => {
+                write!(f, "day21: {} refers to unknown monkey {}", parent, name)
+            }
+            CompileError::Cycle(names) => write!(f, "day21: cycle {}", names.join(" -> ")),
+        }
+    }
 }
 
 fn to_monkey(line: &str) -> (String, Expression) {
     let parts = line.trim().split([':', ' ']).collect::<Vec<&str>>();
     if parts.len() == 3 {
-        (
+        return (
             parts[0].to_string(),
             Expression::Literal(parts[2].parse().unwrap()),
-        )
-    } else {
-        match parts[3] {
-            "+" => (
-                parts[0].to_string(),
-                Expression::Add(parts[2].to_string(), parts[4].to_string()),
-            ),
-            "-" => (
-                parts[0].to_string(),
-                Expression::Subtract(parts[2].to_string(), parts[4].to_string()),
-            ),
-            "*" => (
-                parts[0].to_string(),
-                Expression::Multiply(parts[2].to_string(), parts[4].to_string()),
-            ),
-            "/" => (
-                parts[0].to_string(),
-                Expression::Divide(parts[2].to_string(), parts[4].to_string()),
-            ),
-            _ => panic!("Invalid input"),
-        }
-    }
-}
-
-fn eval(monkeys: &HashMap<String, Expression>, name: &str) -> i64 {
-    match &monkeys[name] {
-        Expression::Literal(value) => *value,
-        Expression::Add(lhs, rhs) => eval(monkeys, &lhs) + eval(monkeys, &rhs),
-        Expression::Subtract(lhs, rhs) => eval(monkeys, &lhs) - eval(monkeys, &rhs),
-        Expression::Multiply(lhs, rhs) => eval(monkeys, &lhs) * eval(monkeys, &rhs),
-        Expression::Divide(lhs, rhs) => eval(monkeys, &lhs) / eval(monkeys, &rhs),
-    }
-}
-
-fn has_child(monkeys: &HashMap<String, Expression>, name: &str, child: &str) -> bool {
-    match &monkeys[name] {
-        _ if name == child => true,
-        Expression::Literal(_) => false,
-        Expression::Add(lhs, rhs)
-        | Expression::Subtract(lhs, rhs)
-        | Expression::Multiply(lhs, rhs)
-        | Expression::Divide(lhs, rhs) => {
-            has_child(monkeys, lhs, child) || has_child(monkeys, rhs, child)
-        }
-    }
-}
-
-fn make_equal(monkeys: &HashMap<String, Expression>, name: &str, target: i64) -> i64 {
-    if name == "humn" {
-        return target;
-    }
-
-    let (sub_target, child) = match &monkeys[name] {
-        Expression::Add(lhs, rhs)
-        | Expression::Subtract(lhs, rhs)
-        | Expression::Multiply(lhs, rhs)
-        | Expression::Divide(lhs, rhs) => {
-            if has_child(monkeys, lhs, "humn") {
-                (eval(monkeys, rhs), lhs)
-            } else {
-                (eval(monkeys, lhs), rhs)
+        );
+    }
+    let op = match parts[3] {
+        "+" => Op::Add,
+        "-" => Op::Subtract,
+        "*" => Op::Multiply,
+        "/" => Op::Divide,
+        _ => panic!("Invalid input"),
+    };
+    (
+        parts[0].to_string(),
+        Expression::Binary(op, parts[2].to_string(), parts[4].to_string()),
+    )
+}
+
+fn compile(monkeys: Vec<(String, Expression)>) -> Result<Dag, CompileError> {
+    let index: HashMap<String, usize> = monkeys
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| (name.clone(), i))
+        .collect();
+    let lookup = |name: &String, parent: &String| {
+        index
+            .get(name)
+            .copied()
+            .ok_or_else(|| CompileError::Undefined(name.clone(), parent.clone()))
+    };
+    let nodes = monkeys
+        .iter()
+        .map(|(name, expression)| match expression {
+            Expression::Literal(value) => Ok(Node::Literal(*value)),
+            Expression::Binary(op, lhs, rhs) => {
+                Ok(Node::Binary(*op, lookup(lhs, name)?, lookup(rhs, name)?))
             }
+        })
+        .collect::<Result<Vec<Node>, CompileError>>()?;
+    let names: Vec<String> = monkeys.into_iter().map(|(name, _)| name).collect();
+
+    let mut visited = vec![false; nodes.len()];
+    let mut on_stack = vec![false; nodes.len()];
+    let mut order = Vec::with_capacity(nodes.len());
+    for start in 0..nodes.len() {
+        if visited[start] {
+            continue;
         }
-        _ => panic!(),
-    };
+        let mut stack = vec![(start, false)];
+        while let Some((node, expanded)) = stack.pop() {
+            if expanded {
+                on_stack[node] = false;
+                order.push(node);
+                continue;
+            }
+            if visited[node] {
+                continue;
+            }
+            visited[node] = true;
+            on_stack[node] = true;
+            stack.push((node, true));
+            if let Node::Binary(_, lhs, rhs) = nodes[node] {
+                for child in [rhs, lhs] {
+                    if on_stack[child] {
+                        let mut cycle: Vec<String> = stack
+                            .iter()
+                            .filter(|(n, expanded)| *expanded && on_stack[*n])
+                            .map(|(n, _)| names[*n].clone())
+                            .skip_while(|name| *name != names[child])
+                            .collect();
+                        cycle.push(names[child].clone());
+                        return Err(CompileError::Cycle(cycle));
+                    }
+                    if !visited[child] {
+                        stack.push((child, false));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Dag {
+        names,
+        nodes,
+        order,
+        index,
+    })
+}
+
+impl Dag {
+    fn index(&self, name: &str) -> Result<usize, CompileError> {
+        self.index
+            .get(name)
+            .copied()
+            .ok_or_else(|| CompileError::Missing(name.to_string()))
+    }
 
-    match &monkeys[name] {
-        Expression::Add(_, _) => make_equal(monkeys, child, target - sub_target),
-        Expression::Subtract(lhs, _) if lhs == child => {
-            make_equal(monkeys, child, target + sub_target)
+    fn operands(&self, node: usize) -> Option<(usize, usize)> {
+        match self.nodes[node] {
+            Node::Binary(_, lhs, rhs) => Some((lhs, rhs)),
+            Node::Literal(_) => None,
         }
-        Expression::Subtract(_, rhs) if rhs == child => {
-            make_equal(monkeys, child, sub_target - target)
+    }
+
+    fn evaluate<V, E>(
+        &self,
+        root: usize,
+        mut leaf: impl FnMut(usize, i64) -> Result<V, E>,
+        mut combine: impl FnMut(Op, &V, &V) -> Result<V, E>,
+    ) -> Result<V, E> {
+        let mut needed = vec![false; self.nodes.len()];
+        needed[root] = true;
+        for &node in self.order.iter().rev() {
+            if let (true, Some((lhs, rhs))) = (needed[node], self.operands(node)) {
+                needed[lhs] = true;
+                needed[rhs] = true;
+            }
         }
-        Expression::Multiply(_, _) => make_equal(monkeys, child, target / sub_target),
-        Expression::Divide(lhs, _) if lhs == child => {
-            make_equal(monkeys, child, target * sub_target)
+
+        let mut values: Vec<Option<V>> = (0..self.nodes.len()).map(|_| None).collect();
+        for &node in self.order.iter().filter(|&&node| needed[node]) {
+            let value = match self.nodes[node] {
+                Node::Literal(value) => leaf(node, value)?,
+                Node::Binary(op, lhs, rhs) => match (&values[lhs], &values[rhs]) {
+                    (Some(lhs), Some(rhs)) => combine(op, lhs, rhs)?,
+                    _ => unreachable!("{} evaluated before its operands", self.names[node]),
+                },
+            };
+            values[node] = Some(value);
         }
-        Expression::Divide(_, rhs) if rhs == child => {
-            make_equal(monkeys, child, sub_target / target)
+        Ok(values[root].take().unwrap())
+    }
+}
+
+enum SolveError {
+    Overflow(Overflow),
+    NoSolution,
+    InfiniteSolutions,
+    NonLinear(usize),
+    NoIntegerSolution(String),
+}
+
+impl From<Overflow> for SolveError {
+    fn from(error: Overflow) -> Self {
+        SolveError::Overflow(error)
+    }
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SolveError::Overflow(error) => write!(f, "{}", error),
+            SolveError::NoSolution => write!(f, "day21: no value of humn satisfies root"),
+            SolveError::InfiniteSolutions => {
+                write!(f, "day21: every value of humn satisfies root")
+            }
+            SolveError::NonLinear(degree) => {
+                write!(f, "day21: humn appears with degree {}", degree)
+            }
+            SolveError::NoIntegerSolution(value) => {
+                write!(f, "day21: no integer solution (humn = {})", value)
+            }
         }
-        _ => panic!(),
     }
 }
 
-fn part2(monkeys: &HashMap<String, Expression>) -> i64 {
-    let (target, child) = match &monkeys["root"] {
-        Expression::Add(lhs, rhs)
-        | Expression::Subtract(lhs, rhs)
-        | Expression::Multiply(lhs, rhs)
-        | Expression::Divide(lhs, rhs) => {
-            if has_child(monkeys, lhs, "humn") {
-                (eval(monkeys, rhs), lhs)
-            } else {
-                (eval(monkeys, lhs), rhs)
+#[derive(Clone)]
+struct Symbolic<T> {
+    numer: Polynomial<T>,
+    denom: Polynomial<T>,
+    poles: Polynomial<T>,
+}
+
+impl<T: Integer> Symbolic<T> {
+    fn constant(value: T) -> Symbolic<T> {
+        Symbolic::from(Polynomial::constant(Rational::from(value)))
+    }
+
+    fn unknown() -> Symbolic<T> {
+        Symbolic::from(Polynomial::x())
+    }
+
+    fn simplify(
+        numer: Polynomial<T>,
+        denom: Polynomial<T>,
+        poles: Polynomial<T>,
+    ) -> Option<Symbolic<T>> {
+        let poles = poles.checked_lcm(&denom)?;
+        let common = numer.checked_gcd(&denom)?;
+        let (numer, _) = numer.checked_div_rem(&common)?;
+        let (denom, _) = denom.checked_div_rem(&common)?;
+        if denom.is_constant() {
+            let numer = numer.checked_scale(&denom.coeff(0).recip()?)?;
+            Some(Symbolic {
+                poles,
+                ..Symbolic::from(numer)
+            })
+        } else {
+            Some(Symbolic {
+                numer,
+                denom,
+                poles,
+            })
+        }
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        let poles = self.poles.checked_lcm(&other.poles)?;
+        if self.denom == other.denom {
+            return Symbolic::simplify(
+                self.numer.checked_add(&other.numer)?,
+                self.denom.clone(),
+                poles,
+            );
+        }
+        let lhs = self.numer.checked_mul(&other.denom)?;
+        let rhs = other.numer.checked_mul(&self.denom)?;
+        Symbolic::simplify(
+            lhs.checked_add(&rhs)?,
+            self.denom.checked_mul(&other.denom)?,
+            poles,
+        )
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let negated = Symbolic {
+            numer: other.numer.checked_neg()?,
+            ..other.clone()
+        };
+        self.checked_add(&negated)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Symbolic::simplify(
+            self.numer.checked_mul(&other.numer)?,
+            self.denom.checked_mul(&other.denom)?,
+            self.poles.checked_lcm(&other.poles)?,
+        )
+    }
+
+    fn checked_div(&self, other: &Self) -> Option<Self> {
+        if other.numer.is_zero() {
+            return None;
+        }
+        Symbolic::simplify(
+            self.numer.checked_mul(&other.denom)?,
+            self.denom.checked_mul(&other.numer)?,
+            self.poles.checked_lcm(&other.poles)?,
+        )
+    }
+
+    fn is_pole(&self, x: &Rational<T>) -> bool {
+        self.poles.eval(x).is_none_or(|value| value.is_zero())
+    }
+}
+
+impl<T: Integer> From<Polynomial<T>> for Symbolic<T> {
+    fn from(numer: Polynomial<T>) -> Self {
+        Symbolic {
+            numer,
+            denom: Polynomial::constant(Rational::one()),
+            poles: Polynomial::constant(Rational::one()),
+        }
+    }
+}
+
+impl<T: Integer> Display for Symbolic<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.denom.is_constant() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "({}) / ({})", self.numer, self.denom)
+        }
+    }
+}
+
+fn combine<T: Integer>(
+    op: Op,
+    lhs: &Symbolic<T>,
+    rhs: &Symbolic<T>,
+    ops: &Checked,
+) -> Result<Symbolic<T>, Overflow> {
+    let result = match op {
+        Op::Add => lhs.checked_add(rhs),
+        Op::Subtract => lhs.checked_sub(rhs),
+        Op::Multiply => lhs.checked_mul(rhs),
+        _ => lhs.checked_div(rhs),
+    };
+    ops.check(op, lhs, rhs, result)
+}
+
+fn solve<T: Integer>(lhs: &Symbolic<T>, rhs: &Symbolic<T>, ops: &Checked) -> Result<T, SolveError> {
+    let cross = (
+        lhs.numer.checked_mul(&rhs.denom),
+        rhs.numer.checked_mul(&lhs.denom),
+    );
+    let equation = match cross {
+        (Some(a), Some(b)) => ops.check(Op::Subtract, &a, &b, a.checked_sub(&b))?,
+        _ => ops.check(Op::Multiply, lhs, rhs, None)?,
+    };
+    match equation.degree() {
+        None => Err(SolveError::InfiniteSolutions),
+        Some(0) => Err(SolveError::NoSolution),
+        Some(1) => {
+            let (c0, c1) = (equation.coeff(0), equation.coeff(1));
+            let root = c0.checked_neg().and_then(|c| c.checked_div(&c1));
+            let root = ops.check(Op::Divide, &c0, &c1, root)?;
+            if lhs.is_pole(&root) || rhs.is_pole(&root) {
+                return Err(SolveError::NoSolution);
             }
+            root.to_integer()
+                .map_err(|_| SolveError::NoIntegerSolution(root.to_string()))
         }
-        _ => panic!("Invalid"),
+        Some(degree) => Err(SolveError::NonLinear(degree)),
+    }
+}
+
+fn part1<T: Integer>(dag: &Dag, root: usize) -> Result<T, Overflow> {
+    let ops = Checked::new("day21");
+    dag.evaluate(
+        root,
+        |_, value| ops.convert(value),
+        |op, lhs, rhs| match op {
+            Op::Add => ops.add(lhs, rhs),
+            Op::Subtract => ops.sub(lhs, rhs),
+            Op::Multiply => ops.mul(lhs, rhs),
+            _ => ops.div(lhs, rhs),
+        },
+    )
+}
+
+fn part2<T: Integer>(dag: &Dag, root: usize, humn: usize) -> Result<T, SolveError> {
+    let ops = Checked::new("day21");
+    let (lhs, rhs) = dag.operands(root).expect("root must compare two monkeys");
+    let leaf = |node: usize, value: i64| match node == humn {
+        true => Ok(Symbolic::unknown()),
+        false => Ok(Symbolic::constant(ops.convert(value)?)),
     };
-    make_equal(monkeys, child, target)
+    let lhs =
+        dag.evaluate::<Symbolic<T>, Overflow>(lhs, leaf, |op, a, b| combine(op, a, b, &ops))?;
+    let rhs =
+        dag.evaluate::<Symbolic<T>, Overflow>(rhs, leaf, |op, a, b| combine(op, a, b, &ops))?;
+    solve(&lhs, &rhs, &ops)
 }
 
 pub fn run() {
-    let monkeys: HashMap<String, Expression> = read_to_vec("data/day21.txt", to_monkey)
-        .into_iter()
-        .collect();
     println!("== Day 21 ==");
-    println!("Part 1: {}", eval(&monkeys, "root"));
-    println!("Part 2: {}", part2(&monkeys));
+    let dag = match compile(read_to_vec("data/day21.txt", to_monkey)) {
+        Ok(dag) => dag,
+        Err(error) => {
+            println!("Error: {}", error);
+            return;
+        }
+    };
+    let root = match dag.index("root") {
+        Ok(root) => root,
+        Err(error) => {
+            println!("Error: {}", error);
+            return;
+        }
+    };
+    match with_fallback(|| part1::<i64>(&dag, root), || part1::<i128>(&dag, root)) {
+        Ok(value) => println!("Part 1: {}", value),
+        Err(error) => println!("Part 1 error: {}", error),
+    }
+    let humn = match dag.index("humn") {
+        Ok(humn) => humn,
+        Err(error) => {
+            println!("Part 2 error: {}", error);
+            return;
+        }
+    };
+    match with_fallback(
+        || part2::<i64>(&dag, root, humn),
+        || part2::<i128>(&dag, root, humn),
+    ) {
+        Ok(value) => println!("Part 2: {}", value),
+        Err(error) => println!("Part 2 error: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compiled(lines: &[&str]) -> Dag {
+        match compile(lines.iter().map(|line| to_monkey(line)).collect()) {
+            Ok(dag) => dag,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    fn compile_error(lines: &[&str]) -> String {
+        let monkeys = lines.iter().map(|line| to_monkey(line)).collect();
+        compile(monkeys).err().unwrap().to_string()
+    }
+
+    fn solve_for(lines: &[&str]) -> Result<i64, SolveError> {
+        let dag = compiled(lines);
+        let (root, humn) = (dag.index("root"), dag.index("humn"));
+        part2::<i64>(&dag, root.ok().unwrap(), humn.ok().unwrap())
+    }
+
+    #[test]
+    fn example() {
+        let lines = [
+            "root: pppw + sjmn",
+            "dbpl: 5",
+            "cczh: sllz + lgvd",
+            "zczc: 2",
+            "ptdq: humn - dvpt",
+            "dvpt: 3",
+            "lfqf: 4",
+            "humn: 5",
+            "ljgn: 2",
+            "sjmn: drzm * dbpl",
+            "sllz: 4",
+            "pppw: cczh / lfqf",
+            "lgvd: ljgn * ptdq",
+            "drzm: hmdt - zczc",
+            "hmdt: 32",
+        ];
+        let dag = compiled(&lines);
+        let root = dag.index("root").ok().unwrap();
+        assert_eq!(part1::<i64>(&dag, root).ok(), Some(152));
+        assert_eq!(solve_for(&lines).ok(), Some(301));
+    }
+
+    #[test]
+    fn degenerate_equations() {
+        let zero = [
+            "root: left + right",
+            "left: humn - humn",
+            "right: 0",
+            "humn: 1",
+        ];
+        assert!(matches!(
+            solve_for(&zero),
+            Err(SolveError::InfiniteSolutions)
+        ));
+        let constant = [
+            "root: left + right",
+            "left: humn - humn",
+            "right: 5",
+            "humn: 1",
+        ];
+        assert!(matches!(solve_for(&constant), Err(SolveError::NoSolution)));
+        let square = [
+            "root: left + right",
+            "left: humn * humn",
+            "right: 4",
+            "humn: 1",
+        ];
+        assert!(matches!(solve_for(&square), Err(SolveError::NonLinear(2))));
+        let half = [
+            "root: left + right",
+            "left: humn * two",
+            "two: 2",
+            "right: 3",
+            "humn: 1",
+        ];
+        assert!(
+            matches!(solve_for(&half), Err(SolveError::NoIntegerSolution(value)) if value == "3/2")
+        );
+    }
+
+    #[test]
+    fn common_factors_cancel() {
+        let square = [
+            "root: left + right",
+            "left: sqr / humn",
+            "sqr: humn * humn",
+            "right: 7",
+            "humn: 1",
+        ];
+        assert_eq!(solve_for(&square).ok(), Some(7));
+        let ratio = [
+            "root: left + right",
+            "left: ratio * humn",
+            "ratio: plus / plus",
+            "plus: humn + one",
+            "one: 1",
+            "right: 4",
+            "humn: 1",
+        ];
+        assert_eq!(solve_for(&ratio).ok(), Some(4));
+    }
+
+    #[test]
+    fn cancelled_factors_remain_poles() {
+        let square = [
+            "root: left + right",
+            "left: sqr / humn",
+            "sqr: humn * humn",
+            "right: 0",
+            "humn: 1",
+        ];
+        assert!(matches!(solve_for(&square), Err(SolveError::NoSolution)));
+        let ratio = [
+            "root: left + right",
+            "left: ratio * humn",
+            "ratio: plus / plus",
+            "plus: humn + one",
+            "one: 1",
+            "right: -1",
+            "humn: 1",
+        ];
+        assert!(matches!(solve_for(&ratio), Err(SolveError::NoSolution)));
+        let inverse = [
+            "root: left + right",
+            "left: one / humn",
+            "one: 1",
+            "right: 0",
+            "humn: 1",
+        ];
+        assert!(matches!(solve_for(&inverse), Err(SolveError::NoSolution)));
+    }
+
+    #[test]
+    fn compile_reports_cycles() {
+        let lines = ["root: a + one", "a: b * one", "b: a - one", "one: 1"];
+        assert_eq!(compile_error(&lines), "day21: cycle a -> b -> a");
+        assert_eq!(
+            compile_error(&["root: root + root"]),
+            "day21: cycle root -> root"
+        );
+    }
+
+    #[test]
+    fn compile_reports_unknown_names() {
+        assert_eq!(
+            compile_error(&["root: left + rigth", "left: 1", "right: 2"]),
+            "day21: root refers to unknown monkey rigth"
+        );
+        let dag = compiled(&["root: left + right", "left: 1", "right: 2"]);
+        assert_eq!(
+            dag.index("humn").err().unwrap().to_string(),
+            "day21: no monkey named humn"
+        );
+    }
+
+    #[test]
+    fn deep_chains_evaluate_iteratively() {
+        let depth = 200_000;
+        let mut lines = vec![
+            "root: m0 + target".to_string(),
+            format!("target: {}", 2 * depth),
+            format!("m{}: humn - one", depth),
+            "one: 1".to_string(),
+            "humn: 5".to_string(),
+        ];
+        lines.extend((0..depth).map(|i| format!("m{}: m{} + one", i, i + 1)));
+        let lines: Vec<&str> = lines.iter().map(|line| line.as_str()).collect();
+        let dag = compiled(&lines);
+        let root = dag.index("root").ok().unwrap();
+        assert_eq!(part1::<i64>(&dag, root).ok(), Some(3 * depth as i64 + 4));
+    }
 }
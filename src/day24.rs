@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 
 use crate::{
-    maths::Vec2,
+    maths::{number_theory::lcm, Vec2},
     utils::{astar_search, read_to_vec, SearchInfo},
 };
 
@@ -44,6 +44,7 @@ struct Map {
     blizzards: Vec<Blizzard>,
     width: i32,
     height: i32,
+    period: usize,
 }
 
 fn to_tiles(line: &str) -> Vec<char> {
@@ -94,6 +95,7 @@ impl Map {
             blizzards,
             width,
             height,
+            period: lcm(width, height) as usize,
         }
     }
 
@@ -115,7 +117,7 @@ impl Map {
         if self.is_wall(state.pos) {
             false
         } else {
-            open_memo[state.minute].contains(&state.pos)
+            open_memo[state.minute % self.period].contains(&state.pos)
         }
     }
 }
@@ -123,7 +125,7 @@ impl Map {
 impl SearchInfo<State, usize> for Map {
     type Data = Vec<HashSet<Vec2>>;
     fn update(&self, node: &State, open_memo: &mut Self::Data) {
-        for m in open_memo.len()..=node.minute + 1 {
+        for m in open_memo.len()..=(node.minute + 1).min(self.period - 1) {
             let blizzards = self
                 .blizzards
                 .iter()
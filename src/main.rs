@@ -24,6 +24,7 @@ mod day23;
 mod day24;
 mod day25;
 mod maths;
+mod ocr;
 mod utils;
 
 use std::env;
@@ -1,3 +1,12 @@
+pub mod bigint;
+pub mod checked;
+pub mod integer;
+pub mod manhattan;
+pub mod number_theory;
+pub mod numeral;
+pub mod polynomial;
+pub mod rational;
+
 use std::ops::{Add, Div, Mul, Sub};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -0,0 +1,401 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+
+use super::integer::Integer;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+fn trim(magnitude: &mut Vec<u32>) {
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+}
+
+fn compare_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, x) in a.iter().enumerate() {
+        let mut diff = *x as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += 1 << 32;
+            borrow = 1;
+        }
+        result.push(diff as u32);
+    }
+    trim(&mut result);
+    result
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut result = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.iter().enumerate() {
+            let product = *x as u64 * *y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    trim(&mut result);
+    result
+}
+
+fn divmod_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) | a[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    trim(&mut quotient);
+    (quotient, remainder as u32)
+}
+
+fn shift_left(a: &[u32], bits: u32) -> Vec<u32> {
+    if bits == 0 {
+        return a.to_vec();
+    }
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0u32;
+    for x in a {
+        result.push((x << bits) | carry);
+        carry = x >> (32 - bits);
+    }
+    result.push(carry);
+    result
+}
+
+fn shift_right(a: &[u32], bits: u32) -> Vec<u32> {
+    if bits == 0 {
+        return a.to_vec();
+    }
+    let mut result = vec![0u32; a.len()];
+    for i in 0..a.len() {
+        let high = a.get(i + 1).map_or(0, |x| x << (32 - bits));
+        result[i] = (a[i] >> bits) | high;
+    }
+    trim(&mut result);
+    result
+}
+
+fn divmod_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_magnitude(a, b) == Ordering::Less {
+        return (vec![], a.to_vec());
+    }
+    if b.len() == 1 {
+        let (quotient, remainder) = divmod_small(a, b[0]);
+        let mut remainder = vec![remainder];
+        trim(&mut remainder);
+        return (quotient, remainder);
+    }
+
+    let shift = b.last().unwrap().leading_zeros();
+    let divisor = shift_left(b, shift);
+    let divisor = &divisor[..b.len()];
+    let mut dividend = shift_left(a, shift);
+    if dividend.len() == a.len() {
+        dividend.push(0);
+    }
+    let n = divisor.len();
+    let m = dividend.len() - n;
+    let top = divisor[n - 1] as u64;
+    let next = divisor[n - 2] as u64;
+    let mut quotient = vec![0u32; m];
+
+    for j in (0..m).rev() {
+        let numerator = ((dividend[j + n] as u64) << 32) | dividend[j + n - 1] as u64;
+        let mut estimate = numerator / top;
+        let mut remainder = numerator % top;
+        while estimate >= 1 << 32
+            || estimate * next > ((remainder << 32) | dividend[j + n - 2] as u64)
+        {
+            estimate -= 1;
+            remainder += top;
+            if remainder >= 1 << 32 {
+                break;
+            }
+        }
+
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let product = estimate * divisor[i] as u64 + carry;
+            carry = product >> 32;
+            let diff = dividend[i + j] as i64 - (product & 0xffff_ffff) as i64 - borrow;
+            dividend[i + j] = diff as u32;
+            borrow = if diff < 0 { 1 } else { 0 };
+        }
+        let diff = dividend[j + n] as i64 - carry as i64 - borrow;
+        dividend[j + n] = diff as u32;
+
+        if diff < 0 {
+            estimate -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = dividend[i + j] as u64 + divisor[i] as u64 + carry;
+                dividend[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            dividend[j + n] = dividend[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = estimate as u32;
+    }
+
+    trim(&mut quotient);
+    dividend.truncate(n);
+    (quotient, shift_right(&dividend, shift))
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> BigInt {
+        trim(&mut magnitude);
+        BigInt {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    fn divmod(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.magnitude.is_empty() {
+            return None;
+        }
+        let (quotient, remainder) = divmod_magnitude(&self.magnitude, &other.magnitude);
+        Some((
+            BigInt::from_parts(self.negative != other.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::from_parts(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.magnitude.is_empty() {
+            return write!(f, "0");
+        }
+        let mut chunks = vec![];
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = divmod_small(&magnitude, 1_000_000_000);
+            chunks.push(remainder);
+            magnitude = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl Integer for BigInt {
+    fn zero() -> Self {
+        BigInt::from_parts(false, vec![])
+    }
+
+    fn one() -> Self {
+        BigInt::from_parts(false, vec![1])
+    }
+
+    fn from_i64(value: i64) -> Option<Self> {
+        Some(BigInt::from(value))
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0u64, |acc, x| (acc << 32) | *x as u64);
+        match self.negative {
+            true if magnitude <= i64::MIN.unsigned_abs() => Some((magnitude as i64).wrapping_neg()),
+            false if magnitude <= i64::MAX as u64 => Some(magnitude as i64),
+            _ => None,
+        }
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        if self.negative == rhs.negative {
+            return Some(BigInt::from_parts(
+                self.negative,
+                add_magnitude(&self.magnitude, &rhs.magnitude),
+            ));
+        }
+        Some(match compare_magnitude(&self.magnitude, &rhs.magnitude) {
+            Ordering::Less => {
+                BigInt::from_parts(rhs.negative, sub_magnitude(&rhs.magnitude, &self.magnitude))
+            }
+            _ => BigInt::from_parts(
+                self.negative,
+                sub_magnitude(&self.magnitude, &rhs.magnitude),
+            ),
+        })
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        self.checked_add(&rhs.checked_neg()?)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(BigInt::from_parts(
+            self.negative != rhs.negative,
+            mul_magnitude(&self.magnitude, &rhs.magnitude),
+        ))
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        self.divmod(rhs).map(|(quotient, _)| quotient)
+    }
+
+    fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+        self.divmod(rhs).map(|(_, remainder)| remainder)
+    }
+
+    fn checked_neg(&self) -> Option<Self> {
+        Some(BigInt::from_parts(!self.negative, self.magnitude.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(value: i128) -> BigInt {
+        let magnitude = value.unsigned_abs();
+        BigInt::from_parts(
+            value < 0,
+            (0..4).map(|i| (magnitude >> (32 * i)) as u32).collect(),
+        )
+    }
+
+    fn samples() -> Vec<i128> {
+        let mut samples = vec![0, 1, -1, 7, -7, 1 << 32, -(1 << 32), (1 << 64) + 3];
+        samples.extend([
+            i64::MAX as i128,
+            i64::MIN as i128,
+            0xffff_ffff,
+            -0x1_0000_0001,
+        ]);
+        samples.extend([
+            (1 << 96) - 1,
+            -(1 << 100) + 12345,
+            0x1234_5678_9abc_def0_1357_9bdf,
+        ]);
+        samples
+    }
+
+    #[test]
+    fn arithmetic_matches_i128() {
+        for a in samples() {
+            for b in samples() {
+                let (x, y) = (big(a), big(b));
+                if let Some(sum) = a.checked_add(b) {
+                    assert_eq!(x.checked_add(&y), Some(big(sum)), "{} + {}", a, b);
+                }
+                if let Some(difference) = a.checked_sub(b) {
+                    assert_eq!(x.checked_sub(&y), Some(big(difference)), "{} - {}", a, b);
+                }
+                if let Some(product) = a.checked_mul(b) {
+                    assert_eq!(x.checked_mul(&y), Some(big(product)), "{} * {}", a, b);
+                }
+                if b != 0 {
+                    assert_eq!(x.checked_div(&y), Some(big(a / b)), "{} / {}", a, b);
+                    assert_eq!(x.checked_rem(&y), Some(big(a % b)), "{} % {}", a, b);
+                }
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+            }
+        }
+    }
+
+    #[test]
+    fn long_division() {
+        let factor = big(0x1_0000_0000_0000_0001);
+        let mut value = BigInt::one();
+        for _ in 0..6 {
+            value = value.checked_mul(&factor).unwrap();
+        }
+        let remainder = big(0xdead_beef_cafe);
+        let dividend = value.checked_add(&remainder).unwrap();
+        let mut quotient = BigInt::one();
+        for _ in 0..5 {
+            quotient = quotient.checked_mul(&factor).unwrap();
+        }
+        assert_eq!(dividend.checked_div(&factor), Some(quotient));
+        assert_eq!(dividend.checked_rem(&factor), Some(remainder));
+        assert_eq!(dividend.checked_div(&BigInt::zero()), None);
+    }
+
+    #[test]
+    fn display_and_conversion() {
+        for value in samples() {
+            assert_eq!(big(value).to_string(), value.to_string());
+        }
+        assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(big(i64::MAX as i128 + 1).to_i64(), None);
+        assert_eq!(big(i64::MIN as i128 - 1).to_i64(), None);
+        assert_eq!(big(-0).to_string(), "0");
+        assert_eq!(
+            BigInt::from(-5).checked_add(&BigInt::from(5)),
+            Some(BigInt::zero())
+        );
+    }
+}
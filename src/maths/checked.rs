@@ -0,0 +1,169 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::integer::Integer;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Op {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Convert,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Overflow {
+    pub day: &'static str,
+    pub op: Op,
+    pub lhs: String,
+    pub rhs: String,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Checked {
+    day: &'static str,
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let symbol = match self {
+            Op::Add => "+",
+            Op::Subtract => "-",
+            Op::Multiply => "*",
+            Op::Divide => "/",
+            Op::Remainder => "%",
+            Op::Convert => "as",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let problem = match self.op {
+            Op::Divide | Op::Remainder if self.rhs == "0" => "division by zero",
+            Op::Convert => "value out of range",
+            _ => "overflow",
+        };
+        write!(
+            f,
+            "{}: {} in {} {} {}",
+            self.day, problem, self.lhs, self.op, self.rhs
+        )
+    }
+}
+
+impl Checked {
+    pub fn new(day: &'static str) -> Checked {
+        Checked { day }
+    }
+
+    pub fn check<L: Display, R: Display, V>(
+        &self,
+        op: Op,
+        lhs: &L,
+        rhs: &R,
+        result: Option<V>,
+    ) -> Result<V, Overflow> {
+        result.ok_or_else(|| Overflow {
+            day: self.day,
+            op,
+            lhs: lhs.to_string(),
+            rhs: rhs.to_string(),
+        })
+    }
+
+    pub fn add<T: Integer>(&self, lhs: &T, rhs: &T) -> Result<T, Overflow> {
+        self.check(Op::Add, lhs, rhs, lhs.checked_add(rhs))
+    }
+
+    pub fn sub<T: Integer>(&self, lhs: &T, rhs: &T) -> Result<T, Overflow> {
+        self.check(Op::Subtract, lhs, rhs, lhs.checked_sub(rhs))
+    }
+
+    pub fn mul<T: Integer>(&self, lhs: &T, rhs: &T) -> Result<T, Overflow> {
+        self.check(Op::Multiply, lhs, rhs, lhs.checked_mul(rhs))
+    }
+
+    pub fn div<T: Integer>(&self, lhs: &T, rhs: &T) -> Result<T, Overflow> {
+        self.check(Op::Divide, lhs, rhs, lhs.checked_div(rhs))
+    }
+
+    pub fn rem<T: Integer>(&self, lhs: &T, rhs: &T) -> Result<T, Overflow> {
+        self.check(Op::Remainder, lhs, rhs, lhs.checked_rem(rhs))
+    }
+
+    pub fn convert<T: Integer>(&self, value: i64) -> Result<T, Overflow> {
+        T::from_i64(value).ok_or_else(|| Overflow {
+            day: self.day,
+            op: Op::Convert,
+            lhs: value.to_string(),
+            rhs: std::any::type_name::<T>().to_string(),
+        })
+    }
+
+    pub fn as_i64<T: Integer>(&self, value: &T) -> Result<i64, Overflow> {
+        value.to_i64().ok_or_else(|| Overflow {
+            day: self.day,
+            op: Op::Convert,
+            lhs: value.to_string(),
+            rhs: "i64".to_string(),
+        })
+    }
+}
+
+pub fn with_fallback<A: Display, B: Display, E>(
+    narrow: impl FnOnce() -> Result<A, E>,
+    wide: impl FnOnce() -> Result<B, E>,
+) -> Result<String, E> {
+    match narrow() {
+        Ok(value) => Ok(value.to_string()),
+        Err(_) => wide().map(|value| value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflow_names_day_and_operation() {
+        let ops = Checked::new("day99");
+        let error = ops.mul(&i64::MAX, &2).unwrap_err();
+        assert_eq!(error.op, Op::Multiply);
+        assert_eq!(
+            error.to_string(),
+            "day99: overflow in 9223372036854775807 * 2"
+        );
+        let error = ops.div(&7i64, &0).unwrap_err();
+        assert_eq!(error.to_string(), "day99: division by zero in 7 / 0");
+        let error = ops.convert::<u32>(-1).unwrap_err();
+        assert_eq!(error.to_string(), "day99: value out of range in -1 as u32");
+        let error = ops.as_i64(&(1i128 << 70)).unwrap_err();
+        assert_eq!(error.op, Op::Convert);
+    }
+
+    #[test]
+    fn successful_operations() {
+        let ops = Checked::new("day99");
+        assert_eq!(ops.add(&2i64, &3), Ok(5));
+        assert_eq!(ops.sub(&2i64, &3), Ok(-1));
+        assert_eq!(ops.rem(&7i64, &3), Ok(1));
+        assert_eq!(ops.as_i64(&42i128), Ok(42));
+    }
+
+    #[test]
+    fn fallback_uses_wide_type_on_overflow() {
+        let ops = Checked::new("day99");
+        let square = |x: i64| ops.mul(&x, &x);
+        let wide = |x: i64| ops.mul(&(x as i128), &(x as i128));
+        assert_eq!(with_fallback(|| square(3), || wide(3)), Ok("9".to_string()));
+        let big = 1i64 << 40;
+        assert_eq!(
+            with_fallback(|| square(big), || wide(big)),
+            Ok((1i128 << 80).to_string())
+        );
+        assert!(with_fallback(|| square(big), || square(big)).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt::{Debug, Display};
+
+pub trait Integer: Clone + Ord + Debug + Display {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn from_i64(value: i64) -> Option<Self>;
+    fn to_i64(&self) -> Option<i64>;
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+    fn checked_sub(&self, rhs: &Self) -> Option<Self>;
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+    fn checked_div(&self, rhs: &Self) -> Option<Self>;
+    fn checked_rem(&self, rhs: &Self) -> Option<Self>;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    fn is_negative(&self) -> bool {
+        *self < Self::zero()
+    }
+
+    fn checked_neg(&self) -> Option<Self> {
+        Self::zero().checked_sub(self)
+    }
+
+    fn checked_abs(&self) -> Option<Self> {
+        if self.is_negative() {
+            self.checked_neg()
+        } else {
+            Some(self.clone())
+        }
+    }
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn one() -> Self {
+                    1
+                }
+
+                fn from_i64(value: i64) -> Option<Self> {
+                    value.try_into().ok()
+                }
+
+                fn to_i64(&self) -> Option<i64> {
+                    (*self).try_into().ok()
+                }
+
+                fn checked_add(&self, rhs: &Self) -> Option<Self> {
+                    <$t>::checked_add(*self, *rhs)
+                }
+
+                fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+                    <$t>::checked_sub(*self, *rhs)
+                }
+
+                fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+                    <$t>::checked_mul(*self, *rhs)
+                }
+
+                fn checked_div(&self, rhs: &Self) -> Option<Self> {
+                    <$t>::checked_div(*self, *rhs)
+                }
+
+                fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+                    <$t>::checked_rem(*self, *rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i32, i64, i128, u32, u64, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_operations_detect_overflow() {
+        assert_eq!(Integer::checked_add(&i64::MAX, &1), None);
+        assert_eq!(Integer::checked_mul(&(1i64 << 32), &(1i64 << 31)), None);
+        assert_eq!(Integer::checked_sub(&0u32, &1), None);
+        assert_eq!(Integer::checked_div(&1i32, &0), None);
+        assert_eq!(Integer::checked_rem(&i64::MIN, &-1), None);
+        assert_eq!(
+            Integer::checked_mul(&(1i128 << 62), &(1i128 << 62)),
+            Some(1i128 << 124)
+        );
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(<u32 as Integer>::from_i64(-1), None);
+        assert_eq!(<i32 as Integer>::from_i64(1 << 40), None);
+        assert_eq!(
+            <i128 as Integer>::from_i64(i64::MIN),
+            Some(i64::MIN as i128)
+        );
+        assert_eq!((1i128 << 63).to_i64(), None);
+        assert_eq!(u64::MAX.to_i64(), None);
+    }
+
+    #[test]
+    fn sign_helpers() {
+        assert!((-3i64).is_negative());
+        assert!(!0u64.is_negative());
+        assert!(0usize.is_zero());
+        assert_eq!((-3i64).checked_neg(), Some(3));
+        assert_eq!(i64::MIN.checked_abs(), None);
+        assert_eq!(3u32.checked_neg(), None);
+        assert_eq!(0u32.checked_neg(), Some(0));
+    }
+}
@@ -0,0 +1,312 @@
+use super::rational::Rational;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Diamond {
+    pub x: i64,
+    pub y: i64,
+    pub r: i64,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Rect {
+    pub u0: i64,
+    pub u1: i64,
+    pub v0: i64,
+    pub v1: i64,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Diagonal {
+    U(i64),
+    V(i64),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Region {
+    rects: Vec<Rect>,
+}
+
+pub fn distance(a: (i64, i64), b: (i64, i64)) -> i64 {
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+pub fn to_rotated(x: i64, y: i64) -> (i64, i64) {
+    (x + y, x - y)
+}
+
+#[allow(dead_code)]
+pub fn from_rotated(u: i64, v: i64) -> Option<(i64, i64)> {
+    if (u - v).rem_euclid(2) == 0 {
+        Some(((u + v) / 2, (u - v) / 2))
+    } else {
+        None
+    }
+}
+
+fn count_parity(lo: i64, hi: i64, parity: i64) -> i64 {
+    if hi < lo {
+        return 0;
+    }
+    let first = if lo.rem_euclid(2) == parity {
+        lo
+    } else {
+        lo + 1
+    };
+    if first > hi {
+        0
+    } else {
+        (hi - first) / 2 + 1
+    }
+}
+
+impl Diamond {
+    pub fn new(x: i64, y: i64, r: i64) -> Diamond {
+        Diamond { x, y, r }
+    }
+
+    pub fn from_points(center: (i64, i64), edge: (i64, i64)) -> Diamond {
+        Diamond::new(center.0, center.1, distance(center, edge))
+    }
+
+    pub fn row_span(&self, y: i64) -> Option<(i64, i64)> {
+        match (self.y - y).abs() {
+            dy if dy > self.r => None,
+            dy => Some((self.x - (self.r - dy), self.x + (self.r - dy))),
+        }
+    }
+
+    pub fn gap_to(&self, other: &Diamond) -> i64 {
+        distance((self.x, self.y), (other.x, other.y)) - self.r - other.r
+    }
+
+    pub fn rotated(&self) -> Rect {
+        let (u, v) = to_rotated(self.x, self.y);
+        Rect {
+            u0: u - self.r,
+            u1: u + self.r,
+            v0: v - self.r,
+            v1: v + self.r,
+        }
+    }
+
+    pub fn separator(&self, other: &Diamond) -> Option<Diagonal> {
+        let (a, b) = (self.rotated(), other.rotated());
+        let lane = |lo0: i64, hi0: i64, lo1: i64, hi1: i64| {
+            if hi0 + 2 == lo1 {
+                Some(hi0 + 1)
+            } else if hi1 + 2 == lo0 {
+                Some(hi1 + 1)
+            } else {
+                None
+            }
+        };
+        match (lane(a.u0, a.u1, b.u0, b.u1), lane(a.v0, a.v1, b.v0, b.v1)) {
+            (Some(u), None) => Some(Diagonal::U(u)),
+            (None, Some(v)) => Some(Diagonal::V(v)),
+            _ => None,
+        }
+    }
+}
+
+impl Diagonal {
+    pub fn intersect_exact(&self, other: &Diagonal) -> Option<(Rational<i64>, Rational<i64>)> {
+        match (self, other) {
+            (Diagonal::U(u), Diagonal::V(v)) | (Diagonal::V(v), Diagonal::U(u)) => {
+                Some((Rational::new(u + v, 2), Rational::new(u - v, 2)))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn intersect(&self, other: &Diagonal) -> Option<(i64, i64)> {
+        let (x, y) = self.intersect_exact(other)?;
+        Some((x.to_integer().ok()?, y.to_integer().ok()?))
+    }
+}
+
+impl Rect {
+    pub fn is_empty(&self) -> bool {
+        self.u0 > self.u1 || self.v0 > self.v1
+    }
+
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let rect = Rect {
+            u0: self.u0.max(other.u0),
+            u1: self.u1.min(other.u1),
+            v0: self.v0.max(other.v0),
+            v1: self.v1.min(other.v1),
+        };
+        if rect.is_empty() {
+            None
+        } else {
+            Some(rect)
+        }
+    }
+
+    pub fn contains(&self, u: i64, v: i64) -> bool {
+        self.u0 <= u && u <= self.u1 && self.v0 <= v && v <= self.v1
+    }
+
+    pub fn subtract(&self, other: &Rect) -> Vec<Rect> {
+        let overlap = match self.intersect(other) {
+            Some(overlap) => overlap,
+            None => return vec![*self],
+        };
+        [
+            Rect {
+                u1: overlap.u0 - 1,
+                ..*self
+            },
+            Rect {
+                u0: overlap.u1 + 1,
+                ..*self
+            },
+            Rect {
+                u0: overlap.u0,
+                u1: overlap.u1,
+                v1: overlap.v0 - 1,
+                ..*self
+            },
+            Rect {
+                u0: overlap.u0,
+                u1: overlap.u1,
+                v0: overlap.v1 + 1,
+                ..*self
+            },
+        ]
+        .into_iter()
+        .filter(|r| !r.is_empty())
+        .collect()
+    }
+
+    pub fn lattice_points(&self) -> i64 {
+        if self.is_empty() {
+            return 0;
+        }
+        let even = count_parity(self.u0, self.u1, 0) * count_parity(self.v0, self.v1, 0);
+        let odd = count_parity(self.u0, self.u1, 1) * count_parity(self.v0, self.v1, 1);
+        even + odd
+    }
+}
+
+impl Region {
+    pub fn new() -> Region {
+        Region::default()
+    }
+
+    pub fn from_diamonds(diamonds: &[Diamond]) -> Region {
+        let mut region = Region::new();
+        for diamond in diamonds {
+            region.add(diamond.rotated());
+        }
+        region
+    }
+
+    pub fn add(&mut self, rect: Rect) {
+        self.subtract(rect);
+        self.rects.push(rect);
+    }
+
+    pub fn subtract(&mut self, rect: Rect) {
+        self.rects = self.rects.iter().flat_map(|r| r.subtract(&rect)).collect();
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        let (u, v) = to_rotated(x, y);
+        self.rects.iter().any(|r| r.contains(u, v))
+    }
+
+    #[allow(dead_code)]
+    pub fn lattice_points(&self) -> i64 {
+        self.rects.iter().map(|r| r.lattice_points()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn covered(diamonds: &[Diamond], x: i64, y: i64) -> bool {
+        diamonds.iter().any(|d| distance((d.x, d.y), (x, y)) <= d.r)
+    }
+
+    #[test]
+    fn diamond_area_matches_lattice_points() {
+        for r in 0..6 {
+            let rect = Diamond::new(3, -2, r).rotated();
+            assert_eq!(rect.lattice_points(), 2 * r * r + 2 * r + 1);
+        }
+    }
+
+    #[test]
+    fn region_union_counts_each_point_once() {
+        let diamonds = [
+            Diamond::new(0, 0, 3),
+            Diamond::new(2, 1, 2),
+            Diamond::new(-4, 3, 1),
+            Diamond::new(1, -1, 0),
+        ];
+        let region = Region::from_diamonds(&diamonds);
+        let mut expected = 0;
+        for x in -10..=10 {
+            for y in -10..=10 {
+                assert_eq!(region.contains(x, y), covered(&diamonds, x, y));
+                expected += covered(&diamonds, x, y) as i64;
+            }
+        }
+        assert_eq!(region.lattice_points(), expected);
+    }
+
+    #[test]
+    fn region_subtract_removes_points() {
+        let mut region = Region::from_diamonds(&[Diamond::new(0, 0, 3)]);
+        region.subtract(Diamond::new(1, 0, 1).rotated());
+        assert!(!region.contains(1, 0));
+        assert!(!region.contains(2, 0));
+        assert!(region.contains(-1, 0));
+        assert_eq!(region.lattice_points(), 25 - 5);
+    }
+
+    #[test]
+    fn rect_subtract_without_overlap_keeps_rect() {
+        let a = Diamond::new(0, 0, 1).rotated();
+        let b = Diamond::new(10, 10, 1).rotated();
+        assert_eq!(a.subtract(&b), vec![a]);
+        assert!(a.subtract(&a).is_empty());
+    }
+
+    #[test]
+    fn rotated_coordinates_round_trip() {
+        for x in -5..=5 {
+            for y in -5..=5 {
+                let (u, v) = to_rotated(x, y);
+                assert_eq!(from_rotated(u, v), Some((x, y)));
+            }
+        }
+        assert_eq!(from_rotated(3, 0), None);
+        assert_eq!(from_rotated(-3, 2), None);
+        assert_eq!(from_rotated(-3, 1), Some((-1, -2)));
+    }
+
+    #[test]
+    fn row_span_of_diamond() {
+        let diamond = Diamond::from_points((2, 2), (4, 3));
+        assert_eq!(diamond.r, 3);
+        assert_eq!(diamond.row_span(2), Some((-1, 5)));
+        assert_eq!(diamond.row_span(5), Some((2, 2)));
+        assert_eq!(diamond.row_span(6), None);
+    }
+
+    #[test]
+    fn separator_and_intersection() {
+        let a = Diamond::new(0, 0, 2);
+        let b = Diamond::new(4, 1, 1);
+        assert_eq!(a.gap_to(&b), 2);
+        assert_eq!(a.separator(&b), Some(Diagonal::U(3)));
+        assert_eq!(Diagonal::U(3).intersect(&Diagonal::V(1)), Some((2, 1)));
+        assert_eq!(Diagonal::U(3).intersect(&Diagonal::V(0)), None);
+        assert_eq!(Diagonal::U(3).intersect(&Diagonal::U(5)), None);
+        let (x, y) = Diagonal::U(3).intersect_exact(&Diagonal::V(0)).unwrap();
+        assert_eq!((x, y), (Rational::new(3, 2), Rational::new(3, 2)));
+    }
+}
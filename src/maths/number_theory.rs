@@ -0,0 +1,299 @@
+use std::fmt::{Display, Formatter, Result};
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use super::integer::Integer;
+
+pub fn gcd<T: Integer>(a: T, b: T) -> Option<T> {
+    let (mut a, mut b) = (a, b);
+    while !b.is_zero() {
+        let r = a.checked_rem(&b).unwrap_or_else(T::zero);
+        a = b;
+        b = r;
+    }
+    a.checked_abs()
+}
+
+pub fn checked_lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a.is_zero() || b.is_zero() {
+        return Some(T::zero());
+    }
+    let g = gcd(a.clone(), b.clone())?;
+    a.checked_div(&g)?.checked_mul(&b)?.checked_abs()
+}
+
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    checked_lcm(a, b).expect("lcm overflow")
+}
+
+fn ext_gcd_wide(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut s0, mut s1) = (1i128, 0i128);
+    let (mut t0, mut t1) = (0i128, 1i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    if r0 < 0 {
+        (-r0, -s0, -t0)
+    } else {
+        (r0, s0, t0)
+    }
+}
+
+#[allow(dead_code)]
+pub fn ext_gcd(a: i64, b: i64) -> Option<(i64, i64, i64)> {
+    let (g, x, y) = ext_gcd_wide(a as i128, b as i128);
+    Some((g.try_into().ok()?, x.try_into().ok()?, y.try_into().ok()?))
+}
+
+fn mod_inverse_wide(a: i128, m: i128) -> Option<i128> {
+    if m <= 0 {
+        return None;
+    }
+    match ext_gcd_wide(a.rem_euclid(m), m) {
+        (1, x, _) => Some(x.rem_euclid(m)),
+        _ => None,
+    }
+}
+
+#[allow(dead_code)]
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    mod_inverse_wide(a as i128, m as i128).map(|x| x as i64)
+}
+
+#[allow(dead_code)]
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut residue = 0i128;
+    let mut modulus = 1i128;
+    for &(r, m) in congruences {
+        if m <= 0 {
+            return None;
+        }
+        let (r, m) = ((r as i128).rem_euclid(m as i128), m as i128);
+        let g = gcd(modulus, m)?;
+        let diff = r - residue;
+        if diff % g != 0 {
+            return None;
+        }
+        let step = m / g;
+        let inverse = mod_inverse_wide(modulus / g, step)?;
+        let k = ((diff / g).rem_euclid(step) * inverse).rem_euclid(step);
+        let combined = modulus.checked_mul(step)?;
+        residue = (residue + modulus * k).rem_euclid(combined);
+        modulus = combined;
+        if modulus > i64::MAX as i128 {
+            return None;
+        }
+    }
+    Some((residue as i64, modulus as i64))
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct ModInt<const M: u64>(u64);
+
+#[allow(dead_code)]
+impl<const M: u64> ModInt<M> {
+    pub fn new(value: i64) -> ModInt<M> {
+        ModInt((value as i128).rem_euclid(M as i128) as u64)
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    pub fn pow(&self, exponent: u64) -> ModInt<M> {
+        let mut result = ModInt(1 % M);
+        let mut base = *self;
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    fn product(self, other: Self) -> ModInt<M> {
+        ModInt(((self.0 as u128 * other.0 as u128) % M as u128) as u64)
+    }
+
+    pub fn inverse(&self) -> Option<ModInt<M>> {
+        mod_inverse_wide(self.0 as i128, M as i128).map(|x| ModInt(x as u64))
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        ModInt(value % M)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        ModInt(((self.0 as u128 + other.0 as u128) % M as u128) as u64)
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        ModInt(((self.0 as u128 + M as u128 - other.0 as u128) % M as u128) as u64)
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.product(other)
+    }
+}
+
+impl<const M: u64> Div for ModInt<M> {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        self.product(other.inverse().expect("divisor is not invertible"))
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        ModInt(0) - self
+    }
+}
+
+impl<const M: u64> AddAssign for ModInt<M> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other
+    }
+}
+
+impl<const M: u64> SubAssign for ModInt<M> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other
+    }
+}
+
+impl<const M: u64> MulAssign for ModInt<M> {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other
+    }
+}
+
+impl<const M: u64> Display for ModInt<M> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_is_non_negative() {
+        assert_eq!(gcd(12i64, 18), Some(6));
+        assert_eq!(gcd(-12i64, 18), Some(6));
+        assert_eq!(gcd(12i64, -18), Some(6));
+        assert_eq!(gcd(0i64, 7), Some(7));
+        assert_eq!(gcd(0i64, 0), Some(0));
+    }
+
+    #[test]
+    fn gcd_handles_minimum_value() {
+        assert_eq!(gcd(i64::MIN, -1), Some(1));
+        assert_eq!(gcd(-1, i64::MIN), Some(1));
+        assert_eq!(gcd(i64::MIN, 6), Some(2));
+        assert_eq!(gcd(0, i64::MIN), None);
+        assert_eq!(gcd(i64::MIN, i64::MIN), None);
+        assert_eq!(checked_lcm(i64::MIN, i64::MIN), None);
+    }
+
+    #[test]
+    fn lcm_of_non_coprime_values() {
+        assert_eq!(lcm(4i64, 6), 12);
+        assert_eq!(lcm(-4i64, 6), 12);
+        assert_eq!(lcm(0i64, 6), 0);
+        assert_eq!([2u64, 3, 4, 6, 8].into_iter().fold(1, lcm), 24);
+    }
+
+    #[test]
+    fn checked_lcm_reports_overflow() {
+        assert_eq!(checked_lcm(i64::MAX, i64::MAX - 1), None);
+        assert_eq!(checked_lcm(1u32 << 31, 3), None);
+        assert_eq!(checked_lcm(1u32 << 30, 2), Some(1 << 30));
+    }
+
+    #[test]
+    fn ext_gcd_gives_bezout_coefficients() {
+        for (a, b) in [(240i64, 46), (-240, 46), (17, 5), (0, 9), (12, 0)] {
+            let (g, x, y) = ext_gcd(a, b).unwrap();
+            assert_eq!(Some(g), gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+        }
+        assert_eq!(ext_gcd(i64::MIN, 0), None);
+    }
+
+    #[test]
+    fn mod_inverse_exists_only_for_coprime_values() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_inverse(5, 0), None);
+        assert_eq!(mod_inverse(i64::MAX - 1, i64::MAX), Some(i64::MAX - 1));
+    }
+
+    #[test]
+    fn crt_combines_congruences() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(-1, 4), (0, 3)]), Some((3, 12)));
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(1, 0)]), None);
+    }
+
+    #[test]
+    fn crt_with_non_coprime_moduli() {
+        assert_eq!(crt(&[(3, 4), (5, 6)]), Some((11, 12)));
+        assert_eq!(crt(&[(2, 6), (8, 10), (2, 4)]), Some((38, 60)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(i64::MAX - 1, i64::MAX), (0, 2)]), None);
+    }
+
+    #[test]
+    fn mod_int_arithmetic() {
+        type M7 = ModInt<7>;
+        let (a, b) = (M7::new(5), M7::new(-4));
+        assert_eq!(b.value(), 3);
+        assert_eq!((a + b).value(), 1);
+        assert_eq!((b - a).value(), 5);
+        assert_eq!((a * b).value(), 1);
+        assert_eq!((-a).value(), 2);
+        assert_eq!((a / b) * b, a);
+        assert_eq!(M7::from(100).to_string(), "2");
+    }
+
+    #[test]
+    fn mod_int_inverse_and_pow() {
+        type P = ModInt<1_000_000_007>;
+        let a = P::new(123_456_789);
+        assert_eq!(a * a.inverse().unwrap(), P::new(1));
+        assert_eq!(a.pow(1_000_000_006), P::new(1));
+        assert_eq!(a.pow(1_000_000_005), a.inverse().unwrap());
+        assert_eq!(P::new(2).pow(10).value(), 1024);
+        assert_eq!(a.pow(0).value(), 1);
+        assert_eq!(ModInt::<1>::new(5).pow(0).value(), 0);
+        assert_eq!(ModInt::<12>::new(8).inverse(), None);
+        assert_eq!(ModInt::<12>::new(0).inverse(), None);
+    }
+}
@@ -0,0 +1,392 @@
+use std::fmt::{self, Display, Formatter};
+
+const STANDARD_SYMBOLS: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NumeralError {
+    InvalidRadix(u32),
+    InvalidDigitSet,
+    InvalidDigit(char, usize),
+    Empty,
+    Overflow,
+}
+
+#[derive(Debug, Clone)]
+pub struct NumeralSystem {
+    radix: i64,
+    min: i64,
+    symbols: Vec<char>,
+    case_insensitive: bool,
+}
+
+impl Display for NumeralError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            NumeralError::InvalidRadix(radix) => write!(f, "invalid radix {}", radix),
+            NumeralError::InvalidDigitSet => write!(f, "invalid digit set"),
+            NumeralError::InvalidDigit(c, index) => {
+                write!(f, "invalid digit '{}' at position {}", c, index)
+            }
+            NumeralError::Empty => write!(f, "empty numeral"),
+            NumeralError::Overflow => write!(f, "value does not fit in 128 bits"),
+        }
+    }
+}
+
+impl NumeralSystem {
+    pub fn new(digits: &[(char, i64)]) -> Result<NumeralSystem, NumeralError> {
+        let radix = digits.len() as i64;
+        if radix < 2 {
+            return Err(NumeralError::InvalidDigitSet);
+        }
+        let min = digits.iter().map(|(_, v)| *v).min().unwrap();
+        let max = min + radix - 1;
+        if min > 0 || (min < 0 && max < 1) {
+            return Err(NumeralError::InvalidDigitSet);
+        }
+        let mut symbols = vec![None; radix as usize];
+        for (c, value) in digits {
+            if *value > max || digits.iter().filter(|(d, _)| d == c).count() > 1 {
+                return Err(NumeralError::InvalidDigitSet);
+            }
+            if symbols[(value - min) as usize].replace(*c).is_some() {
+                return Err(NumeralError::InvalidDigitSet);
+            }
+        }
+        Ok(NumeralSystem {
+            radix,
+            min,
+            symbols: symbols.into_iter().map(|s| s.unwrap()).collect(),
+            case_insensitive: false,
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn standard(radix: u32) -> Result<NumeralSystem, NumeralError> {
+        if !(2..=36).contains(&radix) {
+            return Err(NumeralError::InvalidRadix(radix));
+        }
+        let digits = STANDARD_SYMBOLS
+            .chars()
+            .take(radix as usize)
+            .zip(0..)
+            .collect::<Vec<(char, i64)>>();
+        let mut system = NumeralSystem::new(&digits)?;
+        system.case_insensitive = true;
+        Ok(system)
+    }
+
+    pub fn balanced_quinary() -> NumeralSystem {
+        NumeralSystem::new(&[('=', -2), ('-', -1), ('0', 0), ('1', 1), ('2', 2)]).unwrap()
+    }
+
+    #[allow(dead_code)]
+    pub fn balanced_ternary() -> NumeralSystem {
+        NumeralSystem::new(&[('-', -1), ('0', 0), ('+', 1)]).unwrap()
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.min < 0
+    }
+
+    fn value_of(&self, c: char) -> Option<i64> {
+        let c = if self.case_insensitive {
+            c.to_ascii_lowercase()
+        } else {
+            c
+        };
+        self.symbols
+            .iter()
+            .position(|s| *s == c)
+            .map(|i| i as i64 + self.min)
+    }
+
+    fn symbol(&self, value: i64) -> char {
+        self.symbols[(value - self.min) as usize]
+    }
+
+    fn choose(&self, value: i64) -> i64 {
+        self.min + (value - self.min).rem_euclid(self.radix)
+    }
+
+    fn to_digits(&self, numeral: &str) -> Result<Vec<i64>, NumeralError> {
+        let (negative, body, offset) = match numeral.strip_prefix('-') {
+            Some(rest) if !self.is_signed() => (true, rest, 1),
+            _ => (false, numeral, 0),
+        };
+        if body.is_empty() {
+            return Err(NumeralError::Empty);
+        }
+        body.chars()
+            .rev()
+            .enumerate()
+            .map(|(i, c)| match self.value_of(c) {
+                Some(value) if negative => Ok(-value),
+                Some(value) => Ok(value),
+                None => Err(NumeralError::InvalidDigit(c, offset + body.len() - 1 - i)),
+            })
+            .collect()
+    }
+
+    fn carry(&self, digits: &[i64]) -> (Vec<i64>, i64) {
+        let mut result = Vec::with_capacity(digits.len() + 1);
+        let mut carry = 0;
+        for d in digits {
+            let sum = d + carry;
+            let digit = self.choose(sum);
+            result.push(digit);
+            carry = (sum - digit) / self.radix;
+        }
+        (result, carry)
+    }
+
+    fn render(&self, digits: &[i64]) -> String {
+        let (mut result, mut carry) = self.carry(digits);
+        if carry < 0 && !self.is_signed() {
+            let negated = digits.iter().map(|d| -d).collect::<Vec<i64>>();
+            return format!("-{}", self.render(&negated));
+        }
+        while carry != 0 {
+            let digit = self.choose(carry);
+            result.push(digit);
+            carry = (carry - digit) / self.radix;
+        }
+        while result.len() > 1 && *result.last().unwrap() == 0 {
+            result.pop();
+        }
+        if result.is_empty() {
+            result.push(0);
+        }
+        result.iter().rev().map(|d| self.symbol(*d)).collect()
+    }
+
+    pub fn parse(&self, numeral: &str) -> Result<i128, NumeralError> {
+        let digits = self.to_digits(numeral)?;
+        let value = digits.iter().rev().fold(0i128, |acc, d| {
+            acc.wrapping_mul(self.radix as i128)
+                .wrapping_add(*d as i128)
+        });
+        if self.format(value) == self.render(&digits) {
+            Ok(value)
+        } else {
+            Err(NumeralError::Overflow)
+        }
+    }
+
+    pub fn format(&self, value: i128) -> String {
+        if value < 0 && !self.is_signed() {
+            let mut magnitude = value.unsigned_abs();
+            let mut digits = vec![];
+            while magnitude > 0 {
+                digits.push(self.symbol((magnitude % self.radix as u128) as i64));
+                magnitude /= self.radix as u128;
+            }
+            return std::iter::once('-')
+                .chain(digits.into_iter().rev())
+                .collect();
+        }
+
+        let radix = self.radix as i128;
+        let mut n = value;
+        let mut digits = vec![];
+        while n != 0 {
+            let r = n.rem_euclid(radix);
+            let digit = self.choose(r as i64) as i128;
+            digits.push(self.symbol(digit as i64));
+            n = n.div_euclid(radix) + (r - digit) / radix;
+        }
+        if digits.is_empty() {
+            digits.push(self.symbol(0));
+        }
+        digits.into_iter().rev().collect()
+    }
+
+    pub fn sum<'a, I: IntoIterator<Item = &'a str>>(
+        &self,
+        numerals: I,
+    ) -> Result<String, NumeralError> {
+        let mut total: Vec<i64> = vec![];
+        for numeral in numerals {
+            let digits = self.to_digits(numeral)?;
+            if digits.len() > total.len() {
+                total.resize(digits.len(), 0);
+            }
+            for (t, d) in total.iter_mut().zip(digits) {
+                *t += d;
+            }
+            if total.iter().any(|t| t.abs() > i64::MAX / 4) {
+                let (digits, carry) = self.carry(&total);
+                total = digits;
+                total.push(carry);
+            }
+        }
+        Ok(self.render(&total))
+    }
+
+    #[allow(dead_code)]
+    pub fn normalize(&self, numeral: &str) -> Result<String, NumeralError> {
+        Ok(self.render(&self.to_digits(numeral)?))
+    }
+
+    #[allow(dead_code)]
+    pub fn neg(&self, numeral: &str) -> Result<String, NumeralError> {
+        let digits = self.to_digits(numeral)?;
+        Ok(self.render(&digits.iter().map(|d| -d).collect::<Vec<i64>>()))
+    }
+
+    #[allow(dead_code)]
+    pub fn add(&self, lhs: &str, rhs: &str) -> Result<String, NumeralError> {
+        self.sum([lhs, rhs])
+    }
+
+    #[allow(dead_code)]
+    pub fn sub(&self, lhs: &str, rhs: &str) -> Result<String, NumeralError> {
+        self.add(lhs, &self.neg(rhs)?)
+    }
+
+    #[allow(dead_code)]
+    pub fn mul(&self, lhs: &str, rhs: &str) -> Result<String, NumeralError> {
+        let a = self.to_digits(lhs)?;
+        let b = self.to_digits(rhs)?;
+        let mut product = vec![0i64; a.len() + b.len()];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                product[i + j] += x * y;
+            }
+            let (digits, carry) = self.carry(&product);
+            product = digits;
+            product.push(carry);
+        }
+        Ok(self.render(&product))
+    }
+
+    #[allow(dead_code)]
+    pub fn convert(&self, numeral: &str, target: &NumeralSystem) -> Result<String, NumeralError> {
+        Ok(target.format(self.parse(numeral)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snafu_round_trip() {
+        let snafu = NumeralSystem::balanced_quinary();
+        for (value, numeral) in [
+            (0, "0"),
+            (1, "1"),
+            (3, "1="),
+            (8, "2="),
+            (2022, "1=11-2"),
+            (-3, "-2"),
+        ] {
+            assert_eq!(snafu.format(value), numeral);
+            assert_eq!(snafu.parse(numeral), Ok(value));
+        }
+        for value in -500..=500 {
+            assert_eq!(snafu.parse(&snafu.format(value)), Ok(value));
+        }
+    }
+
+    #[test]
+    fn extreme_values_round_trip() {
+        let snafu = NumeralSystem::balanced_quinary();
+        let hex = NumeralSystem::standard(16).unwrap();
+        for value in [i128::MIN, i128::MIN + 1, i128::MAX] {
+            assert_eq!(snafu.parse(&snafu.format(value)), Ok(value));
+            assert_eq!(hex.parse(&hex.format(value)), Ok(value));
+        }
+        let too_large = format!("1{}", hex.format(i128::MAX));
+        assert_eq!(hex.parse(&too_large), Err(NumeralError::Overflow));
+    }
+
+    #[test]
+    fn standard_bases() {
+        let hex = NumeralSystem::standard(16).unwrap();
+        assert_eq!(hex.parse("FF"), Ok(255));
+        assert_eq!(hex.parse("-ff"), Ok(-255));
+        assert_eq!(hex.format(-255), "-ff");
+        assert_eq!(
+            NumeralSystem::standard(1).err(),
+            Some(NumeralError::InvalidRadix(1))
+        );
+        assert_eq!(
+            NumeralSystem::standard(37).err(),
+            Some(NumeralError::InvalidRadix(37))
+        );
+    }
+
+    #[test]
+    fn invalid_numerals() {
+        let snafu = NumeralSystem::balanced_quinary();
+        assert_eq!(snafu.parse(""), Err(NumeralError::Empty));
+        assert_eq!(snafu.parse("1=3"), Err(NumeralError::InvalidDigit('3', 2)));
+        assert!(NumeralSystem::new(&[('a', 0)]).is_err());
+        assert!(NumeralSystem::new(&[('a', 0), ('a', 1)]).is_err());
+        assert!(NumeralSystem::new(&[('a', 1), ('b', 2)]).is_err());
+    }
+
+    #[test]
+    fn sum_on_digit_strings() {
+        let snafu = NumeralSystem::balanced_quinary();
+        let numbers = [
+            "1=-0-2", "12111", "2=0=", "21", "2=01", "111", "20012", "112", "1=-1=", "1-12", "12",
+            "1=", "122",
+        ];
+        assert_eq!(snafu.sum(numbers), Ok("2=-1=0".to_string()));
+        assert_eq!(snafu.sum(["1", "-1"]), Ok("-2".to_string()));
+        assert_eq!(snafu.sum(["2", "="]), Ok("0".to_string()));
+    }
+
+    #[test]
+    fn convert_between_systems() {
+        let snafu = NumeralSystem::balanced_quinary();
+        let decimal = NumeralSystem::standard(10).unwrap();
+        assert_eq!(snafu.convert("2=-1=0", &decimal), Ok("4890".to_string()));
+        assert_eq!(decimal.convert("-4890", &snafu), Ok("=21-20".to_string()));
+    }
+
+    #[test]
+    fn balanced_ternary_round_trip() {
+        let ternary = NumeralSystem::balanced_ternary();
+        for (value, numeral) in [
+            (0, "0"),
+            (1, "+"),
+            (-1, "-"),
+            (2, "+-"),
+            (8, "+0-"),
+            (-8, "-0+"),
+        ] {
+            assert_eq!(ternary.format(value), numeral);
+            assert_eq!(ternary.parse(numeral), Ok(value));
+        }
+        for value in -1000..=1000 {
+            assert_eq!(ternary.parse(&ternary.format(value)), Ok(value));
+        }
+        assert_eq!(ternary.normalize("00+-"), Ok("+-".to_string()));
+        assert_eq!(ternary.neg("+0-"), Ok("-0+".to_string()));
+    }
+
+    #[test]
+    fn arithmetic_on_digit_strings_matches_i128() {
+        let snafu = NumeralSystem::balanced_quinary();
+        let hex = NumeralSystem::standard(16).unwrap();
+        let values = [0i128, 1, -1, 7, -42, 2022, 4890, -123456789, 1 << 40];
+        for system in [&snafu, &hex] {
+            for a in values {
+                for b in values {
+                    let (x, y) = (system.format(a), system.format(b));
+                    assert_eq!(system.add(&x, &y), Ok(system.format(a + b)));
+                    assert_eq!(system.sub(&x, &y), Ok(system.format(a - b)));
+                    assert_eq!(system.mul(&x, &y), Ok(system.format(a * b)));
+                }
+            }
+        }
+        assert_eq!(snafu.mul("1=", "2="), Ok("10-".to_string()));
+        assert_eq!(
+            snafu.sub("1", "1=3"),
+            Err(NumeralError::InvalidDigit('3', 2))
+        );
+    }
+}
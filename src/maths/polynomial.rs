@@ -0,0 +1,239 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::integer::Integer;
+use super::rational::Rational;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Polynomial<T> {
+    coeffs: Vec<Rational<T>>,
+}
+
+impl<T: Integer> Polynomial<T> {
+    pub fn new(coeffs: Vec<Rational<T>>) -> Polynomial<T> {
+        let mut polynomial = Polynomial { coeffs };
+        polynomial.trim();
+        polynomial
+    }
+
+    pub fn zero() -> Polynomial<T> {
+        Polynomial { coeffs: vec![] }
+    }
+
+    pub fn constant(value: Rational<T>) -> Polynomial<T> {
+        Polynomial::new(vec![value])
+    }
+
+    pub fn x() -> Polynomial<T> {
+        Polynomial::new(vec![Rational::zero(), Rational::one()])
+    }
+
+    fn trim(&mut self) {
+        while self.coeffs.last().is_some_and(|c| c.is_zero()) {
+            self.coeffs.pop();
+        }
+    }
+
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.coeffs.len() <= 1
+    }
+
+    pub fn coeff(&self, power: usize) -> Rational<T> {
+        self.coeffs
+            .get(power)
+            .cloned()
+            .unwrap_or_else(Rational::zero)
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let len = self.coeffs.len().max(other.coeffs.len());
+        let coeffs = (0..len)
+            .map(|i| self.coeff(i).checked_add(&other.coeff(i)))
+            .collect::<Option<Vec<Rational<T>>>>()?;
+        Some(Polynomial::new(coeffs))
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        let coeffs = self
+            .coeffs
+            .iter()
+            .map(|c| c.checked_neg())
+            .collect::<Option<Vec<Rational<T>>>>()?;
+        Some(Polynomial { coeffs })
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&other.checked_neg()?)
+    }
+
+    pub fn checked_scale(&self, factor: &Rational<T>) -> Option<Self> {
+        let coeffs = self
+            .coeffs
+            .iter()
+            .map(|c| c.checked_mul(factor))
+            .collect::<Option<Vec<Rational<T>>>>()?;
+        Some(Polynomial::new(coeffs))
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        if self.is_zero() || other.is_zero() {
+            return Some(Polynomial::zero());
+        }
+        let mut coeffs = vec![Rational::zero(); self.coeffs.len() + other.coeffs.len() - 1];
+        for (i, a) in self.coeffs.iter().enumerate() {
+            for (j, b) in other.coeffs.iter().enumerate() {
+                coeffs[i + j] = coeffs[i + j].checked_add(&a.checked_mul(b)?)?;
+            }
+        }
+        Some(Polynomial::new(coeffs))
+    }
+
+    pub fn checked_div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        let degree = divisor.degree()?;
+        let lead = divisor.coeff(degree).recip()?;
+        let mut quotient = vec![Rational::zero(); self.coeffs.len().saturating_sub(degree)];
+        let mut remainder = self.clone();
+        while let Some(top) = remainder.degree().filter(|top| *top >= degree) {
+            let factor = remainder.coeff(top).checked_mul(&lead)?;
+            let mut term = vec![Rational::zero(); top - degree + 1];
+            term[top - degree] = factor.clone();
+            quotient[top - degree] = factor;
+            remainder = remainder.checked_sub(&divisor.checked_mul(&Polynomial::new(term))?)?;
+        }
+        Some((Polynomial::new(quotient), remainder))
+    }
+
+    pub fn monic(&self) -> Option<Self> {
+        match self.degree() {
+            Some(degree) => self.checked_scale(&self.coeff(degree).recip()?),
+            None => Some(Polynomial::zero()),
+        }
+    }
+
+    pub fn checked_gcd(&self, other: &Self) -> Option<Self> {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let (_, remainder) = a.checked_div_rem(&b)?;
+            a = b;
+            b = remainder;
+        }
+        a.monic()
+    }
+
+    pub fn checked_lcm(&self, other: &Self) -> Option<Self> {
+        if self.is_zero() || other.is_zero() {
+            return Some(Polynomial::zero());
+        }
+        let (quotient, _) = self.checked_div_rem(&self.checked_gcd(other)?)?;
+        quotient.checked_mul(other)?.monic()
+    }
+
+    pub fn eval(&self, x: &Rational<T>) -> Option<Rational<T>> {
+        self.coeffs
+            .iter()
+            .rev()
+            .try_fold(Rational::zero(), |acc, c| {
+                acc.checked_mul(x)?.checked_add(c)
+            })
+    }
+}
+
+impl<T: Integer> From<Rational<T>> for Polynomial<T> {
+    fn from(value: Rational<T>) -> Self {
+        Polynomial::constant(value)
+    }
+}
+
+impl<T: Integer> Display for Polynomial<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        for (power, coeff) in self.coeffs.iter().enumerate().rev() {
+            if coeff.is_zero() {
+                continue;
+            }
+            let negative = *coeff < Rational::zero();
+            let magnitude = if negative {
+                -coeff.clone()
+            } else {
+                coeff.clone()
+            };
+            match (power + 1 == self.coeffs.len(), negative) {
+                (true, true) => write!(f, "-")?,
+                (true, false) => {}
+                (false, true) => write!(f, " - ")?,
+                (false, false) => write!(f, " + ")?,
+            }
+            if power == 0 || magnitude != Rational::one() {
+                write!(f, "{}", magnitude)?;
+            }
+            match power {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{}", power)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poly(coeffs: &[i64]) -> Polynomial<i64> {
+        Polynomial::new(coeffs.iter().map(|c| Rational::from(*c)).collect())
+    }
+
+    #[test]
+    fn arithmetic_and_display() {
+        let p = poly(&[1, 1]);
+        let q = poly(&[-1, 0, 2]);
+        assert_eq!(p.to_string(), "x + 1");
+        assert_eq!(q.to_string(), "2x^2 - 1");
+        assert_eq!(p.checked_add(&q).unwrap(), poly(&[0, 1, 2]));
+        assert_eq!(p.checked_sub(&p).unwrap().degree(), None);
+        assert_eq!(p.checked_mul(&q).unwrap(), poly(&[-1, -1, 2, 2]));
+        assert_eq!(q.eval(&Rational::new(1, 2)).unwrap(), Rational::new(-1, 2));
+        assert!(poly(&[i64::MAX]).checked_add(&poly(&[1])).is_none());
+    }
+
+    #[test]
+    fn division_with_remainder() {
+        let (quotient, remainder) = poly(&[-1, 0, 0, 1])
+            .checked_div_rem(&poly(&[-1, 1]))
+            .unwrap();
+        assert_eq!(quotient, poly(&[1, 1, 1]));
+        assert!(remainder.is_zero());
+        let (quotient, remainder) = poly(&[1, 0, 1]).checked_div_rem(&poly(&[0, 2])).unwrap();
+        assert_eq!(
+            quotient,
+            Polynomial::new(vec![Rational::zero(), Rational::new(1, 2)])
+        );
+        assert_eq!(remainder, poly(&[1]));
+        assert!(poly(&[1]).checked_div_rem(&Polynomial::zero()).is_none());
+    }
+
+    #[test]
+    fn gcd_and_lcm_are_monic() {
+        let a = poly(&[2, 2]).checked_mul(&poly(&[-3, 1])).unwrap();
+        let b = poly(&[3, 3]).checked_mul(&poly(&[0, 0, 1])).unwrap();
+        assert_eq!(a.checked_gcd(&b).unwrap(), poly(&[1, 1]));
+        assert_eq!(a.checked_gcd(&poly(&[5])).unwrap(), poly(&[1]));
+        assert_eq!(
+            poly(&[0, 4]).checked_gcd(&Polynomial::zero()).unwrap(),
+            Polynomial::x()
+        );
+        let lcm = poly(&[1, 1]).checked_lcm(&poly(&[-1, 1])).unwrap();
+        assert_eq!(lcm, poly(&[-1, 0, 1]));
+        assert_eq!(poly(&[2]).checked_lcm(&poly(&[3])).unwrap(), poly(&[1]));
+    }
+}
@@ -0,0 +1,326 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use super::integer::Integer;
+use super::number_theory::gcd;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Rational<T> {
+    numer: T,
+    denom: T,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RationalError {
+    ZeroDenominator,
+    Overflow,
+    NotInteger(String),
+}
+
+impl Display for RationalError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RationalError::ZeroDenominator => write!(f, "zero denominator"),
+            RationalError::Overflow => write!(f, "rational overflow"),
+            RationalError::NotInteger(value) => write!(f, "{} is not an integer", value),
+        }
+    }
+}
+
+fn floor_div_rem<T: Integer>(numer: &T, denom: &T) -> Option<(T, T)> {
+    let q = numer.checked_div(denom)?;
+    let r = numer.checked_rem(denom)?;
+    if r.is_negative() {
+        Some((q.checked_sub(&T::one())?, r.checked_add(denom)?))
+    } else {
+        Some((q, r))
+    }
+}
+
+impl<T: Integer> Rational<T> {
+    pub fn try_new(numer: T, denom: T) -> Result<Rational<T>, RationalError> {
+        if denom.is_zero() {
+            return Err(RationalError::ZeroDenominator);
+        }
+        if numer.is_zero() {
+            return Ok(Rational::zero());
+        }
+        let g = gcd(numer.clone(), denom.clone()).ok_or(RationalError::Overflow)?;
+        let (numer, denom) = match (numer.checked_div(&g), denom.checked_div(&g)) {
+            (Some(n), Some(d)) => (n, d),
+            _ => return Err(RationalError::Overflow),
+        };
+        if denom.is_negative() {
+            match (numer.checked_neg(), denom.checked_neg()) {
+                (Some(numer), Some(denom)) => Ok(Rational { numer, denom }),
+                _ => Err(RationalError::Overflow),
+            }
+        } else {
+            Ok(Rational { numer, denom })
+        }
+    }
+
+    pub fn new(numer: T, denom: T) -> Rational<T> {
+        match Rational::try_new(numer, denom) {
+            Ok(value) => value,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    pub fn from_integer(value: T) -> Rational<T> {
+        Rational {
+            numer: value,
+            denom: T::one(),
+        }
+    }
+
+    pub fn zero() -> Rational<T> {
+        Rational::from_integer(T::zero())
+    }
+
+    pub fn one() -> Rational<T> {
+        Rational::from_integer(T::one())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer.is_zero()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == T::one()
+    }
+
+    pub fn to_integer(&self) -> Result<T, RationalError> {
+        if self.is_integer() {
+            Ok(self.numer.clone())
+        } else {
+            Err(RationalError::NotInteger(self.to_string()))
+        }
+    }
+
+    pub fn recip(&self) -> Option<Rational<T>> {
+        Rational::try_new(self.denom.clone(), self.numer.clone()).ok()
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let g = gcd(self.denom.clone(), other.denom.clone())?;
+        let lhs = self.numer.checked_mul(&other.denom.checked_div(&g)?)?;
+        let rhs = other.numer.checked_mul(&self.denom.checked_div(&g)?)?;
+        let denom = self.denom.checked_div(&g)?.checked_mul(&other.denom)?;
+        Rational::try_new(lhs.checked_add(&rhs)?, denom).ok()
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Rational {
+            numer: self.numer.checked_neg()?,
+            denom: self.denom.clone(),
+        })
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&other.checked_neg()?)
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let g0 = gcd(self.numer.clone(), other.denom.clone())?;
+        let g1 = gcd(other.numer.clone(), self.denom.clone())?;
+        let numer = self
+            .numer
+            .checked_div(&g0)?
+            .checked_mul(&other.numer.checked_div(&g1)?)?;
+        let denom = self
+            .denom
+            .checked_div(&g1)?
+            .checked_mul(&other.denom.checked_div(&g0)?)?;
+        Rational::try_new(numer, denom).ok()
+    }
+
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        self.checked_mul(&other.recip()?)
+    }
+}
+
+impl<T: Integer> From<T> for Rational<T> {
+    fn from(value: T) -> Self {
+        Rational::from_integer(value)
+    }
+}
+
+impl<T: Integer> Ord for Rational<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (q0, r0) = floor_div_rem(&self.numer, &self.denom).unwrap();
+        let (q1, r1) = floor_div_rem(&other.numer, &other.denom).unwrap();
+        match (q0.cmp(&q1), r0.is_zero(), r1.is_zero()) {
+            (Ordering::Equal, true, true) => Ordering::Equal,
+            (Ordering::Equal, true, false) => Ordering::Less,
+            (Ordering::Equal, false, true) => Ordering::Greater,
+            (Ordering::Equal, false, false) => {
+                let lhs = Rational::new(self.denom.clone(), r0);
+                let rhs = Rational::new(other.denom.clone(), r1);
+                rhs.cmp(&lhs)
+            }
+            (ordering, _, _) => ordering,
+        }
+    }
+}
+
+impl<T: Integer> PartialOrd for Rational<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Integer> Display for Rational<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+impl<T: Integer> Add for Rational<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.checked_add(&other).expect("rational overflow")
+    }
+}
+
+impl<T: Integer> Sub for Rational<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.checked_sub(&other).expect("rational overflow")
+    }
+}
+
+impl<T: Integer> Mul for Rational<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.checked_mul(&other).expect("rational overflow")
+    }
+}
+
+impl<T: Integer> Div for Rational<T> {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        if other.is_zero() {
+            panic!("attempt to divide by zero");
+        }
+        self.checked_div(&other).expect("rational overflow")
+    }
+}
+
+impl<T: Integer> Neg for Rational<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.checked_neg().expect("rational overflow")
+    }
+}
+
+impl<T: Integer> AddAssign for Rational<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = self.clone() + other
+    }
+}
+
+impl<T: Integer> SubAssign for Rational<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = self.clone() - other
+    }
+}
+
+impl<T: Integer> MulAssign for Rational<T> {
+    fn mul_assign(&mut self, other: Self) {
+        *self = self.clone() * other
+    }
+}
+
+impl<T: Integer> DivAssign for Rational<T> {
+    fn div_assign(&mut self, other: Self) {
+        *self = self.clone() / other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalization() {
+        assert_eq!(Rational::new(6i64, -4).to_string(), "-3/2");
+        assert_eq!(Rational::new(-6i64, -3), Rational::from(2));
+        assert_eq!(Rational::new(0i64, -5), Rational::zero());
+        assert_eq!(
+            Rational::try_new(1i64, 0),
+            Err(RationalError::ZeroDenominator)
+        );
+    }
+
+    #[test]
+    fn minimum_value_does_not_panic() {
+        assert_eq!(Rational::try_new(0, i64::MIN), Ok(Rational::zero()));
+        assert_eq!(Rational::try_new(1, i64::MIN), Err(RationalError::Overflow));
+        assert_eq!(
+            Rational::try_new(i64::MIN, i64::MIN),
+            Err(RationalError::Overflow)
+        );
+        assert_eq!(
+            Rational::try_new(i64::MIN, 2),
+            Ok(Rational::from(i64::MIN / 2))
+        );
+        let min = Rational::from(i64::MIN);
+        assert_eq!(min.checked_neg(), None);
+        assert_eq!(
+            min.checked_mul(&Rational::new(1, 2)),
+            Some(Rational::from(i64::MIN / 2))
+        );
+        assert_eq!(min.checked_add(&min), None);
+        assert_eq!(min.recip(), None);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let half = Rational::new(1i64, 2);
+        let third = Rational::new(1i64, 3);
+        assert_eq!(half.clone() + third.clone(), Rational::new(5, 6));
+        assert_eq!(half.clone() - third.clone(), Rational::new(1, 6));
+        assert_eq!(half.clone() * third.clone(), Rational::new(1, 6));
+        assert_eq!(half.clone() / third.clone(), Rational::new(3, 2));
+        assert_eq!(-half.clone(), Rational::new(-1, 2));
+        assert_eq!(half.checked_div(&Rational::zero()), None);
+        let mut value = Rational::from(1i64);
+        value += half.clone();
+        value *= Rational::from(4);
+        value -= third.clone();
+        value /= third;
+        assert_eq!(value, Rational::from(17));
+    }
+
+    #[test]
+    fn to_integer_requires_whole_value() {
+        assert_eq!(Rational::new(8i64, 4).to_integer(), Ok(2));
+        assert_eq!(
+            Rational::new(7i64, 4).to_integer(),
+            Err(RationalError::NotInteger("7/4".to_string()))
+        );
+    }
+
+    #[test]
+    fn ordering() {
+        let mut values: Vec<Rational<i64>> = [(1, 2), (-1, 3), (2, 1), (-7, 2), (5, 4), (1, 3)]
+            .into_iter()
+            .map(|(n, d)| Rational::new(n, d))
+            .collect();
+        values.sort();
+        let sorted: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        assert_eq!(sorted, ["-7/2", "-1/3", "1/3", "1/2", "5/4", "2"]);
+        assert!(Rational::new(i64::MAX, i64::MAX - 1) < Rational::new(i64::MAX - 1, i64::MAX - 2));
+    }
+}
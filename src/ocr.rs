@@ -0,0 +1,302 @@
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Font {
+    Small,
+    Large,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OcrError {
+    UnsupportedHeight(usize),
+    UnknownGlyph(usize, String),
+}
+
+const SMALL_GLYPHS: [(char, [&str; 6]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+const LARGE_GLYPHS: [(char, [&str; 10]); 15] = [
+    (
+        'A',
+        [
+            "..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'B',
+        [
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#",
+            "#....#", "#####.",
+        ],
+    ),
+    (
+        'C',
+        [
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#....#", ".####.",
+        ],
+    ),
+    (
+        'E',
+        [
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+    (
+        'F',
+        [
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+    ),
+    (
+        'G',
+        [
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#",
+            "#...##", ".###.#",
+        ],
+    ),
+    (
+        'H',
+        [
+            "#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'J',
+        [
+            "...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.",
+            "#...#.", ".###..",
+        ],
+    ),
+    (
+        'K',
+        [
+            "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..",
+            "#...#.", "#....#",
+        ],
+    ),
+    (
+        'L',
+        [
+            "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+    (
+        'N',
+        [
+            "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##",
+            "#...##", "#....#",
+        ],
+    ),
+    (
+        'P',
+        [
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+    ),
+    (
+        'R',
+        [
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'X',
+        [
+            "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.",
+            "#....#", "#....#",
+        ],
+    ),
+    (
+        'Z',
+        [
+            "######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....",
+            "#.....", "######",
+        ],
+    ),
+];
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            OcrError::UnsupportedHeight(height) => {
+                write!(f, "no font is {} pixels tall", height)
+            }
+            OcrError::UnknownGlyph(index, glyph) => {
+                write!(f, "unknown glyph at position {}:\n{}", index, glyph)
+            }
+        }
+    }
+}
+
+impl Font {
+    fn from_height(height: usize) -> Option<Font> {
+        match height {
+            6 => Some(Font::Small),
+            10 => Some(Font::Large),
+            _ => None,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        match self {
+            Font::Small => 4,
+            Font::Large => 6,
+        }
+    }
+
+    pub fn spacing(&self) -> usize {
+        match self {
+            Font::Small => 1,
+            Font::Large => 2,
+        }
+    }
+
+    fn lookup(&self, glyph: &[String]) -> Option<char> {
+        let matches = |rows: &[&str]| rows.iter().zip(glyph).all(|(a, b)| a == b);
+        match self {
+            Font::Small => SMALL_GLYPHS
+                .iter()
+                .find(|(_, rows)| matches(rows))
+                .map(|(c, _)| *c),
+            Font::Large => LARGE_GLYPHS
+                .iter()
+                .find(|(_, rows)| matches(rows))
+                .map(|(c, _)| *c),
+        }
+    }
+}
+
+fn is_lit(pixel: char) -> bool {
+    matches!(pixel, '#' | '█')
+}
+
+fn glyph_at(pixels: &[Vec<bool>], start: usize, width: usize) -> Vec<String> {
+    pixels
+        .iter()
+        .map(|row| {
+            (start..start + width)
+                .map(|c| match row.get(c) {
+                    Some(true) => '#',
+                    _ => '.',
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn recognize_with(screen: &str, font: Font) -> Result<String, OcrError> {
+    let pixels: Vec<Vec<bool>> = screen
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.chars().map(is_lit).collect())
+        .collect();
+    let columns = pixels.iter().map(|row| row.len()).max().unwrap_or(0);
+    let stride = font.width() + font.spacing();
+
+    let mut letters = String::new();
+    for (index, start) in (0..columns).step_by(stride).enumerate() {
+        let glyph = glyph_at(&pixels, start, font.width());
+        if glyph.iter().all(|row| !row.contains('#')) {
+            continue;
+        }
+        match font.lookup(&glyph) {
+            Some(letter) => letters.push(letter),
+            None => return Err(OcrError::UnknownGlyph(index, glyph.join("\n"))),
+        }
+    }
+
+    Ok(letters)
+}
+
+pub fn recognize(screen: &str) -> Result<String, OcrError> {
+    let height = screen
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count();
+    match Font::from_height(height) {
+        Some(font) => recognize_with(screen, font),
+        None => Err(OcrError::UnsupportedHeight(height)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(text: &str) -> String {
+        let glyphs = text
+            .chars()
+            .map(|c| SMALL_GLYPHS.iter().find(|(l, _)| *l == c).unwrap().1)
+            .collect::<Vec<[&str; 6]>>();
+        (0..6)
+            .map(|row| {
+                glyphs
+                    .iter()
+                    .map(|g| format!("{}.", g[row]))
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn recognizes_small_font() {
+        let screen = render("RFZEKBFA");
+        assert_eq!(screen.lines().next().unwrap().len(), 40);
+        assert_eq!(recognize(&screen), Ok("RFZEKBFA".to_string()));
+        let blocks = screen.replace('#', "█").replace('.', " ");
+        assert_eq!(recognize(&blocks), Ok("RFZEKBFA".to_string()));
+    }
+
+    #[test]
+    fn recognizes_large_font() {
+        let (_, rows) = LARGE_GLYPHS.iter().find(|(c, _)| *c == 'X').unwrap();
+        let screen = format!("\n{}\n", rows.join("\n"));
+        assert_eq!(recognize(&screen), Ok("X".to_string()));
+        assert_eq!(recognize_with(&screen, Font::Large), Ok("X".to_string()));
+    }
+
+    #[test]
+    fn rejects_unsupported_height() {
+        let screen = render("AB");
+        let truncated = screen.lines().take(5).collect::<Vec<&str>>().join("\n");
+        assert_eq!(recognize(&truncated), Err(OcrError::UnsupportedHeight(5)));
+        assert_eq!(recognize(""), Err(OcrError::UnsupportedHeight(0)));
+    }
+
+    #[test]
+    fn reports_unknown_glyph() {
+        let screen = render("AB")
+            .lines()
+            .map(|row| format!("{}#....", row))
+            .collect::<Vec<String>>()
+            .join("\n");
+        let glyph = ["#...", "#...", "#...", "#...", "#...", "#..."].join("\n");
+        assert_eq!(recognize(&screen), Err(OcrError::UnknownGlyph(2, glyph)));
+    }
+}
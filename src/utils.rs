@@ -1,5 +1,5 @@
 use std::{
-    cmp::Ordering,
+    cmp::{Ordering, Reverse},
     collections::{BinaryHeap, HashMap, HashSet},
     fmt::Debug,
     fs::read_to_string,
@@ -30,6 +30,64 @@ pub fn read_some_to_vec<T>(filename: &str, convert: fn(&str) -> Option<T>) -> Ve
     }
 }
 
+pub fn read_groups<T>(filename: &str, convert: fn(&str) -> T) -> Vec<Vec<T>> {
+    let contents = read_to_string(filename).unwrap_or_default();
+    let mut groups = vec![];
+    let mut group = vec![];
+    for line in contents.lines() {
+        match line.trim() {
+            "" if group.is_empty() => {}
+            "" => groups.push(std::mem::take(&mut group)),
+            _ => group.push(convert(line)),
+        }
+    }
+    if !group.is_empty() {
+        groups.push(group);
+    }
+    groups
+}
+
+pub struct TopK<T: Ord> {
+    k: usize,
+    heap: BinaryHeap<Reverse<T>>,
+}
+
+impl<T: Ord> TopK<T> {
+    pub fn new(k: usize) -> TopK<T> {
+        TopK {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        if self.k == 0 {
+            return;
+        }
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(item));
+        } else if let Some(mut smallest) = self.heap.peek_mut() {
+            if item > smallest.0 {
+                smallest.0 = item;
+            }
+        }
+    }
+
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|r| r.0)
+            .collect()
+    }
+}
+
+impl<T: Ord> Extend<T> for TopK<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        items.into_iter().for_each(|item| self.push(item))
+    }
+}
+
 pub fn is_match<T: Eq>(values: &Vec<T>, i: usize, j: usize, length: usize) -> bool {
     (i..i + length)
         .into_iter()
@@ -154,6 +212,44 @@ pub fn astar_search<
     None
 }
 
+pub fn shortest_distances<
+    T: Eq + Hash + Copy,
+    I: Debug + Copy + Ord + PartialOrd + Add<Output = I>,
+    S: SearchInfo<T, I>,
+>(
+    info: &S,
+) -> (HashMap<T, I>, HashMap<T, T>) {
+    let mut data = info.init();
+    let mut came_from = HashMap::new();
+
+    let mut distances = HashMap::new();
+    distances.insert(info.start(), S::zero());
+
+    let mut queue = BinaryHeap::new();
+    queue.push(Ranking(info.start(), S::zero()));
+
+    while let Some(Ranking(current, distance)) = queue.pop() {
+        if distance > distances[&current] {
+            continue;
+        }
+
+        info.update(&current, &mut data);
+        for neighbor in info.neighbors(&current, &data) {
+            let tentative_distance = distance + info.distance(&current, &neighbor);
+            if distances
+                .get(&neighbor)
+                .is_none_or(|known| tentative_distance < *known)
+            {
+                came_from.insert(neighbor, current);
+                distances.insert(neighbor, tentative_distance);
+                queue.push(Ranking(neighbor, tentative_distance));
+            }
+        }
+    }
+
+    (distances, came_from)
+}
+
 pub trait GraphNode {
     fn neighbors(&self) -> Vec<usize>;
 }